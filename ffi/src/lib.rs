@@ -0,0 +1,4 @@
+//!Builds the C library declared in `include/blitz.h`. The functions are defined in the `ffi` module of the blitz crate,
+//! which is only built as a Rust library.
+
+pub use blitz::ffi::*;
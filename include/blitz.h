@@ -0,0 +1,61 @@
+/*
+ * C interface to the blitz game engine. Build the library from the ffi crate:
+ *     cargo build --release -p blitz-ffi
+ * and link against libblitz_ffi.so, libblitz_ffi.dylib or blitz_ffi.dll.
+ *
+ * Prefs, plays and snapshots are protobuf encoded GamePrefs, Play and GameSnapshot messages from protos/main.proto.
+ * Functions that can fail return BLITZ_ERROR (or NULL), and blitz_last_error describes the failure.
+ * Functions that fill a buffer return the size they need, and only write to the buffer if it is large enough.
+ */
+#ifndef BLITZ_H
+#define BLITZ_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BLITZ_ERROR (-1)
+
+#define BLITZ_PILE_HAND 0
+#define BLITZ_PILE_AVAILABLE 1
+#define BLITZ_PILE_BLITZ 2
+#define BLITZ_PILE_POST 3
+#define BLITZ_PILE_ARENA 4
+
+typedef struct BlitzGame BlitzGame;
+
+/* Describes the last failure on the calling thread. Valid until the next failure on the same thread. */
+const char *blitz_last_error(void);
+
+/* Creates a game, dealt the same way for the same seed. Returns NULL on failure. Free it with blitz_game_free. */
+BlitzGame *blitz_game_new(const uint8_t *prefs, size_t prefs_len, uint32_t player_count, uint64_t seed);
+void blitz_game_free(BlitzGame *game);
+
+/* Returns 0, or BLITZ_ERROR if the play is not allowed, in which case nothing changes. */
+int64_t blitz_game_make_play(BlitzGame *game, const uint8_t *play, size_t play_len);
+/* Returns 1 if the round should end, otherwise 0. */
+int64_t blitz_game_should_end_round(BlitzGame *game);
+/* Scores the round and, unless the game is over, deals the next one. */
+int64_t blitz_game_end_round(BlitzGame *game);
+/* Returns 1 if the game is over, otherwise 0. */
+int64_t blitz_game_is_game_over(BlitzGame *game);
+
+/* The number of piles of a kind: the player's post piles, the arena piles, or 1 for the other kinds. */
+int64_t blitz_game_pile_count(BlitzGame *game, uint32_t player, uint32_t kind);
+/* Writes the cards of a pile from the bottom to the top, as indices into the deck. Returns the number of cards.
+ * player is ignored for arena piles, and index is only used for post and arena piles. */
+int64_t blitz_game_pile(BlitzGame *game, uint32_t player, uint32_t kind, uint32_t index, uint32_t *out, size_t capacity);
+
+/* Writes the encoded GlobalDeck, which describes the card behind every index. Returns its size. */
+int64_t blitz_game_deck(BlitzGame *game, uint8_t *out, size_t capacity);
+/* Writes the encoded GameSnapshot. Returns its size. */
+int64_t blitz_game_snapshot(BlitzGame *game, uint8_t *out, size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
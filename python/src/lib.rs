@@ -0,0 +1,4 @@
+//!Builds the `blitz` Python extension module. The bindings are defined in the `python` module of the blitz crate, which is
+//! only built as a Rust library.
+
+pub use blitz::python::*;
@@ -0,0 +1,217 @@
+//!Records the plays of a game as they happen, and replays them once it is over to show each player where they could improve.
+
+use std::time::{Duration, Instant};
+
+use crate::{proto, Action, Arena, GameState, Play, Player, PlayerAction};
+
+///How long an arena or blitz play has to be left unmade before it counts as missed.
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(5);
+
+pub struct GameRecorder {
+    pub rounds: Vec<RoundRecord>,
+}
+
+///The cards dealt for a round, and every play accepted during it.
+pub struct RoundRecord {
+    pub round: u32,
+    pub started: Instant,
+    pub dealt: Vec<Player>,
+    pub plays: Vec<PlayRecord>,
+}
+
+pub struct PlayRecord {
+    pub at: Instant,
+    pub play: Play,
+    ///The draw rate when the play was made, which decides how many cards a transfer moves.
+    pub draw_rate: u32,
+}
+
+impl GameRecorder {
+    ///Starts recording from the current position, which should be the deal of a round.
+    pub fn new(game: &GameState) -> Self {
+        let mut recorder = GameRecorder { rounds: vec![] };
+        recorder.start_round(game);
+        recorder
+    }
+    fn start_round(&mut self, game: &GameState) {
+        self.rounds.push(RoundRecord {
+            round: game.round,
+            started: game.round_started,
+            dealt: game.players.clone(),
+            plays: vec![],
+        });
+    }
+    ///Must be called before each play is made. Starts recording a new round once the game has moved on to one.
+    pub fn observe(&mut self, game: &GameState) {
+        if self.rounds.last().map(|r| r.round) != Some(game.round) {
+            self.start_round(game);
+        }
+    }
+    ///Records a play that the game accepted. Calling blitz is not recorded, since it ends the round.
+    pub fn record(&mut self, game: &GameState, play: Play, at: Instant) {
+        if matches!(play.play, Action::CallBlitz(_)) {
+            return;
+        }
+        if let Some(round) = self.rounds.last_mut() {
+            round.plays.push(PlayRecord {
+                at,
+                play,
+                draw_rate: game.draw_rate,
+            });
+        }
+    }
+    ///Forgets the player's most recent play of the round, once the game has taken it back with [`GameState::undo`].
+    pub fn undo(&mut self, game: &GameState, player: u32) {
+        let Some(round) = self.rounds.last_mut().filter(|r| r.round == game.round) else {
+            return;
+        };
+        if let Some(index) = round.plays.iter().rposition(|r| r.play.player == player) {
+            round.plays.remove(index);
+        }
+    }
+
+    ///Replays every recorded round of `game` to find, for each player, the arena and blitz plays they left unmade for at least
+    /// `idle_threshold`, along with how fast they got cards into the arena and how long they spent cycling their hand.
+    pub fn analyze(&self, game: &GameState, idle_threshold: Duration) -> proto::GameAnalysis {
+        let player_count = game.players.len();
+        let game_started = self.rounds.first().map(|r| r.started);
+        let since_start = |at: Instant| {
+            game_started
+                .map(|s| at.saturating_duration_since(s).as_millis() as u32)
+                .unwrap_or(0)
+        };
+        let mut players: Vec<proto::PlayerAnalysis> = (0..player_count as u32)
+            .map(|player_id| proto::PlayerAnalysis {
+                player_id,
+                ..Default::default()
+            })
+            .collect();
+        let mut missed_opportunities = vec![];
+        let mut played_time = Duration::ZERO;
+
+        for round in self.rounds.iter() {
+            let mut missed = |player: usize, (since, play): (Instant, Action), until: Instant| {
+                let waited = until.saturating_duration_since(since);
+                if waited >= idle_threshold {
+                    missed_opportunities.push((
+                        player,
+                        proto::MissedOpportunity {
+                            round: round.round,
+                            at_ms: since_start(since),
+                            waited_ms: waited.as_millis() as u32,
+                            play: Some(
+                                Play {
+                                    player: player as u32,
+                                    play,
+                                }
+                                .into(),
+                            ),
+                        },
+                    ));
+                }
+            };
+            //the game is usually over by now, so put the replay back to the start of the round
+            let mut replay = game.clone();
+            replay.is_game_over = false;
+            replay.round = round.round;
+            replay.players = round.dealt.clone();
+            replay.arena = Arena::new();
+            replay.locked_until.fill(None);
+            //when each player's first arena or blitz play became available, and what it was
+            let mut waiting = vec![None; player_count];
+            let mut cycling_since = vec![None; player_count];
+            update_waiting(&replay, &mut waiting, round.started);
+
+            let mut last = round.started;
+            for record in round.plays.iter() {
+                let player = record.play.player as usize;
+                if let Some(wait) = waiting[player].take() {
+                    missed(player, wait, record.at);
+                }
+                if let Some(since) = cycling_since[player].take() {
+                    players[player].cycling_ms +=
+                        record.at.saturating_duration_since(since).as_millis() as u32;
+                }
+                match record.play.play {
+                    Action::Player(PlayerAction::TransferToAvailable) => {
+                        players[player].transfers += 1;
+                        cycling_since[player] = Some(record.at);
+                    }
+                    Action::Player(PlayerAction::ResetHand) => {
+                        cycling_since[player] = Some(record.at);
+                    }
+                    Action::Arena(_) => players[player].arena_cards += 1,
+                    _ => {}
+                }
+                replay.draw_rate = record.draw_rate;
+                if replay.make_play(record.play).is_err() {
+                    tracing::warn!(
+                        round = round.round,
+                        "Recorded play could not be replayed: {:?}",
+                        record.play
+                    );
+                    break;
+                }
+                for (player, wait) in update_waiting(&replay, &mut waiting, record.at) {
+                    missed(player, wait, record.at);
+                }
+                last = record.at;
+            }
+            //the round ended with its last play
+            for (player, wait) in waiting.into_iter().enumerate() {
+                if let Some(wait) = wait {
+                    missed(player, wait, last);
+                }
+            }
+            played_time += last.saturating_duration_since(round.started);
+        }
+
+        for (player, missed) in missed_opportunities {
+            players[player].missed_opportunities.push(missed);
+        }
+        let minutes = played_time.as_secs_f32() / 60.0;
+        if minutes > 0.0 {
+            for player in players.iter_mut() {
+                player.cards_per_minute = player.arena_cards as f32 / minutes;
+            }
+        }
+        proto::GameAnalysis {
+            players,
+            played_ms: played_time.as_millis() as u32,
+        }
+    }
+}
+
+///Starts waiting for the players who now have an arena or blitz play, and stops for those who no longer do.
+/// Returns the waits that stopped.
+fn update_waiting(
+    game: &GameState,
+    waiting: &mut [Option<(Instant, Action)>],
+    now: Instant,
+) -> Vec<(usize, (Instant, Action))> {
+    let mut stopped = vec![];
+    for (player, wait) in waiting.iter_mut().enumerate() {
+        match (opportunity(game, player as u32), *wait) {
+            (Some(play), None) => *wait = Some((now, play)),
+            (None, Some(w)) => {
+                stopped.push((player, w));
+                *wait = None;
+            }
+            _ => {}
+        }
+    }
+    stopped
+}
+
+///The first play the player could make into the arena or out of their blitz pile.
+fn opportunity(game: &GameState, player: u32) -> Option<Action> {
+    if game.players.get(player as usize)?.is_eliminated() {
+        return None;
+    }
+    game.legal_moves(player).into_iter().find(|play| {
+        matches!(
+            play,
+            Action::Arena(_) | Action::Player(PlayerAction::BlitzToPost(_))
+        )
+    })
+}
@@ -8,7 +8,12 @@ async fn main() -> anyhow::Result<()> {
         .with_line_number(true)
         .finish();
     tracing::subscriber::set_global_default(tracing_subscriber)?;
-    let server = Server::new();
+    //daily challenge runs are appended to this file, and read back when the server starts
+    let daily_results_path =
+        std::env::var("BLITZ_DAILY_RESULTS").unwrap_or_else(|_| "daily_results.jsonl".to_string());
+    let daily_results =
+        blitz::daily::DailyResults::with_store(blitz::daily::FileStore::open(daily_results_path)?)?;
+    let server = Server::new().with_daily_results(daily_results);
     let session_server =
         blitz::proto::session_service_server::SessionServiceServer::new(server.clone());
     let game_server = blitz::proto::game_service_server::GameServiceServer::new(server);
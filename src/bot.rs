@@ -0,0 +1,244 @@
+//!A computer opponent that picks its moves with Monte Carlo tree search.
+//!
+//! The bot cannot see the order of the other players' hands or the cards under the top of their blitz piles, so every iteration
+//! of the search starts from a determinization: a copy of the game with those hidden cards shuffled. The search runs over the bot's
+//! own moves, with every other player answering each of them with a random move.
+
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::{Action, GameState, Play};
+
+pub struct MctsBot {
+    ///The most search iterations to run for each move.
+    pub iterations: u32,
+    ///When set, stops searching once this much time has been spent on a move, even if iterations are left.
+    pub time_limit: Option<Duration>,
+    ///How many moves each player makes in a random playout after leaving the tree.
+    pub rollout_depth: u32,
+    ///How much the search favors rarely tried moves over ones that have done well.
+    pub exploration: f64,
+    rng: StdRng,
+}
+
+///A node of the search tree. The root has no play.
+struct Node {
+    play: Option<Action>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: u32,
+    reward: f64,
+}
+
+impl MctsBot {
+    pub fn new() -> Self {
+        Self {
+            iterations: 500,
+            time_limit: None,
+            rollout_depth: 8,
+            exploration: std::f64::consts::SQRT_2,
+            rng: StdRng::from_entropy(),
+        }
+    }
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+    pub fn with_rollout_depth(mut self, rollout_depth: u32) -> Self {
+        self.rollout_depth = rollout_depth;
+        self
+    }
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+    ///Makes the bot's choices repeatable.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    ///Picks the move for `player` that did best in the search, or `None` if the player has no legal move.
+    pub fn choose_move(&mut self, game: &GameState, player: u32) -> Option<Action> {
+        let state = game.players.get(player as usize)?;
+        if state.can_call_blitz() {
+            return Some(Action::CallBlitz(player));
+        }
+        let started = Instant::now();
+        let mut tree = vec![Node {
+            play: None,
+            parent: None,
+            children: vec![],
+            visits: 0,
+            reward: 0.0,
+        }];
+        for _ in 0..self.iterations {
+            if self
+                .time_limit
+                .is_some_and(|limit| started.elapsed() >= limit)
+            {
+                break;
+            }
+            let mut state = self.determinize(game, player);
+            let mut node = 0;
+            //selection and expansion
+            while !round_is_over(&state) {
+                let legal: Vec<Action> = state
+                    .legal_moves(player)
+                    .into_iter()
+                    .filter(|play| !matches!(play, Action::CallBlitz(_)))
+                    .collect();
+                if legal.is_empty() {
+                    break;
+                }
+                let untried: Vec<Action> = legal
+                    .iter()
+                    .copied()
+                    .filter(|play| {
+                        !tree[node]
+                            .children
+                            .iter()
+                            .any(|c| tree[*c].play == Some(*play))
+                    })
+                    .collect();
+                let (child, play) = if let Some(play) = untried.choose(&mut self.rng) {
+                    tree.push(Node {
+                        play: Some(*play),
+                        parent: Some(node),
+                        children: vec![],
+                        visits: 0,
+                        reward: 0.0,
+                    });
+                    let child = tree.len() - 1;
+                    tree[node].children.push(child);
+                    (child, *play)
+                } else {
+                    //only the moves that are legal in this determinization can be chosen
+                    let parent_visits = tree[node].visits.max(1) as f64;
+                    let child = tree[node]
+                        .children
+                        .iter()
+                        .copied()
+                        .filter(|c| tree[*c].play.is_some_and(|p| legal.contains(&p)))
+                        .max_by(|a, b| {
+                            let ucb = |n: &Node| {
+                                n.reward / n.visits as f64
+                                    + self.exploration
+                                        * (parent_visits.ln() / n.visits as f64).sqrt()
+                            };
+                            ucb(&tree[*a]).total_cmp(&ucb(&tree[*b]))
+                        })?;
+                    (child, tree[child].play?)
+                };
+                let expanded = tree[child].visits == 0;
+                let _ = state.make_play(Play { player, play });
+                self.play_opponents(&mut state, player);
+                node = child;
+                if expanded {
+                    break;
+                }
+            }
+            //playout
+            for _ in 0..self.rollout_depth {
+                if round_is_over(&state) {
+                    break;
+                }
+                self.play_random(&mut state, player);
+                self.play_opponents(&mut state, player);
+            }
+            //backpropagation
+            let reward = evaluate(&state, player);
+            let mut current = Some(node);
+            while let Some(n) = current {
+                tree[n].visits += 1;
+                tree[n].reward += reward;
+                current = tree[n].parent;
+            }
+        }
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|c| tree[**c].visits)
+            .and_then(|c| tree[*c].play)
+    }
+
+    ///A copy of the game in which the cards that `player` cannot see are shuffled.
+    /// The top cards of the other players' available and blitz piles stay where they are, since they are face up.
+    fn determinize(&mut self, game: &GameState, player: u32) -> GameState {
+        let mut game = game.clone();
+        for other in game.players.iter_mut().filter(|p| p.player_id != player) {
+            let hand = &mut other.hand;
+            let available_top = hand.available_to_play.pop();
+            let blitz_top = other.blitz_pile.cards.pop();
+            let (in_hand, available) = (hand.in_hand.len(), hand.available_to_play.len());
+            let mut hidden: Vec<u32> = hand
+                .in_hand
+                .drain(..)
+                .chain(hand.available_to_play.drain(..))
+                .chain(other.blitz_pile.cards.drain(..))
+                .collect();
+            hidden.shuffle(&mut self.rng);
+            let mut hidden = hidden.into_iter();
+            hand.in_hand = hidden.by_ref().take(in_hand).collect();
+            hand.available_to_play = hidden
+                .by_ref()
+                .take(available)
+                .chain(available_top)
+                .collect();
+            other.blitz_pile.cards = hidden.chain(blitz_top).collect();
+        }
+        game
+    }
+
+    ///Makes one random move that the game accepts, if there is any.
+    fn play_random(&mut self, game: &mut GameState, player: u32) {
+        let mut candidates = game.candidate_moves(player);
+        candidates.retain(|play| !matches!(play, Action::CallBlitz(_)));
+        candidates.shuffle(&mut self.rng);
+        //plays are verified before anything changes, so rejected ones leave the game as it was
+        for play in candidates {
+            if game.make_play(Play { player, play }).is_ok() {
+                return;
+            }
+        }
+    }
+    fn play_opponents(&mut self, game: &mut GameState, player: u32) {
+        for other in 0..game.players.len() as u32 {
+            if other != player && !round_is_over(game) {
+                self.play_random(game, other);
+            }
+        }
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///The round ends as soon as anyone can call blitz.
+fn round_is_over(game: &GameState) -> bool {
+    game.players.iter().any(|p| p.can_call_blitz())
+}
+
+///How good the round looks for `player`, from 0 to 1: their score so far this round compared to the best of the others.
+fn evaluate(game: &GameState, player: u32) -> f64 {
+    let scores = game.round_points();
+    let best_other = scores
+        .iter()
+        .enumerate()
+        .filter(|(p, _)| *p != player as usize)
+        .map(|(_, s)| *s)
+        .max()
+        .unwrap_or(0);
+    let lead = (scores[player as usize] - best_other) as f64;
+    1.0 / (1.0 + (-lead / 5.0).exp())
+}
@@ -0,0 +1,479 @@
+//!A compact copy of a game's cards for bots and simulations, which clone positions and try plays millions of times.
+//!
+//! [`CompactGame`] holds every pile in fixed-size arrays, so it is `Copy` and making a play never allocates. A card is stored as
+//! a `u8` holding its place in its owner's deck, from which its color, number and gender follow: the first 40 cards are
+//! red, blue, green and yellow 1 to 10, and any others are wild. The owner is known from the pile, except in the arena,
+//! where it is stored next to each card.
+//!
+//! Only the cards are copied. Scores, lockouts, undo and anything else that depends on time stays with the [`GameState`],
+//! and calling blitz is left to it as well.
+
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+
+use crate::prefs::{MAX_PLAYERS, MAX_POST_PILE_SIZE, MAX_WILD_CARDS_PER_PLAYER};
+use crate::{proto, Action, ArenaAction, Color, GameState, Pile, Play, PlayerAction};
+
+pub const MAX_DECK_SIZE: usize = 40 + MAX_WILD_CARDS_PER_PLAYER as usize;
+pub const MAX_POST_PILES: usize = MAX_POST_PILE_SIZE as usize;
+///Only a 1 can start an arena pile, and each player has four.
+pub const MAX_ARENA_PILES: usize = 4 * MAX_PLAYERS as usize;
+///Arena and post piles never hold more than 10 cards.
+const PILE_SIZE: usize = 10;
+
+///Why a play was rejected. Unlike the errors of [`GameState::make_play`], these never allocate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayError {
+    NoSuchPlayer,
+    Eliminated,
+    NoSuchPile,
+    PileEmpty,
+    PileFull,
+    WrongColor,
+    WrongNumber,
+    SameGender,
+    SamePile,
+    ///With [`proto::PostRefillRule::ForcedRefill`], empty post piles must be refilled before anything else.
+    RefillFirst,
+    ///Ending the round is left to the [`GameState`].
+    CallBlitz,
+}
+impl Display for PlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PlayError::NoSuchPlayer => "Player does not exist",
+            PlayError::Eliminated => "Player has been eliminated",
+            PlayError::NoSuchPile => "Pile index out of bounds",
+            PlayError::PileEmpty => "Pile is empty",
+            PlayError::PileFull => "Pile is full",
+            PlayError::WrongColor => "Card color does not match the pile",
+            PlayError::WrongNumber => "Card number does not match the pile",
+            PlayError::SameGender => "Genders must alternate",
+            PlayError::SamePile => "Cannot move a post card onto its own pile",
+            PlayError::RefillFirst => "Empty post piles must be refilled from the blitz pile first",
+            PlayError::CallBlitz => "Calling blitz is only possible on a GameState",
+        })
+    }
+}
+impl std::error::Error for PlayError {}
+
+///A pile of at most `N` cards, bottom first.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Stack<const N: usize> {
+    len: u8,
+    cards: [u8; N],
+}
+impl<const N: usize> Stack<N> {
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            cards: [0; N],
+        }
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        &self.cards[..self.len as usize]
+    }
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn top(&self) -> Option<u8> {
+        self.as_slice().last().copied()
+    }
+    fn push(&mut self, card: u8) -> Result<(), PlayError> {
+        let slot = self
+            .cards
+            .get_mut(self.len as usize)
+            .ok_or(PlayError::PileFull)?;
+        *slot = card;
+        self.len += 1;
+        Ok(())
+    }
+    fn pop(&mut self) -> Option<u8> {
+        let card = self.top()?;
+        self.len -= 1;
+        Some(card)
+    }
+    fn from_cards(cards: impl ExactSizeIterator<Item = u8>) -> Result<Self> {
+        if cards.len() > N {
+            return Err(anyhow!("Pile of {} cards does not fit in {N}", cards.len()));
+        }
+        let mut stack = Self::new();
+        for card in cards {
+            stack.cards[stack.len as usize] = card;
+            stack.len += 1;
+        }
+        Ok(stack)
+    }
+}
+impl<const N: usize> Default for Stack<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CompactPile {
+    pub cards: Stack<PILE_SIZE>,
+    pub color: Color,
+}
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CompactArenaPile {
+    pub pile: CompactPile,
+    ///The owner of each card in the pile.
+    pub owners: [u8; PILE_SIZE],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CompactPlayer {
+    pub in_hand: Stack<MAX_DECK_SIZE>,
+    pub available: Stack<MAX_DECK_SIZE>,
+    pub blitz: Stack<MAX_DECK_SIZE>,
+    post: [CompactPile; MAX_POST_PILES],
+    post_len: u8,
+    pub eliminated: bool,
+}
+impl CompactPlayer {
+    pub fn post_piles(&self) -> &[CompactPile] {
+        &self.post[..self.post_len as usize]
+    }
+    fn post_pile(&mut self, pile: u32) -> Result<&mut CompactPile, PlayError> {
+        self.post[..self.post_len as usize]
+            .get_mut(pile as usize)
+            .ok_or(PlayError::NoSuchPile)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct CompactGame {
+    players: [CompactPlayer; MAX_PLAYERS as usize],
+    player_count: u8,
+    arena: [CompactArenaPile; MAX_ARENA_PILES],
+    arena_len: u8,
+    deck_size: u8,
+    pub draw_rate: u32,
+    post_refill_rule: proto::PostRefillRule,
+}
+
+const fn is_wild(card: u8) -> bool {
+    card >= 40
+}
+const fn number(card: u8) -> u8 {
+    card % 10 + 1
+}
+const fn color(card: u8) -> Color {
+    match card / 10 {
+        0 => Color::Red,
+        1 => Color::Blue,
+        2 => Color::Green,
+        _ => Color::Yellow,
+    }
+}
+///0 for boys, 1 for girls.
+const fn gender(card: u8) -> u8 {
+    card % 2
+}
+
+impl CompactPile {
+    const EMPTY: CompactPile = CompactPile {
+        cards: Stack::new(),
+        color: Color::Red,
+    };
+    ///Follows [`Pile::verify_add_arena_card`].
+    fn verify_arena(&self, card: u8) -> Result<(), PlayError> {
+        if self.cards.len() == PILE_SIZE {
+            return Err(PlayError::PileFull);
+        }
+        if is_wild(card) {
+            return Ok(());
+        }
+        if color(card) != self.color {
+            return Err(PlayError::WrongColor);
+        }
+        if number(card) as usize != self.cards.len() + 1 {
+            return Err(PlayError::WrongNumber);
+        }
+        Ok(())
+    }
+    ///Follows [`Pile::verify_add_post_card`]: the number and gender of the topmost normal card, counting each wild card above it
+    /// as the next card down.
+    fn verify_post(&self, card: u8) -> Result<(), PlayError> {
+        if self.cards.len() == PILE_SIZE {
+            return Err(PlayError::PileFull);
+        }
+        if is_wild(card) {
+            return Ok(());
+        }
+        let top = self
+            .cards
+            .as_slice()
+            .iter()
+            .rev()
+            .enumerate()
+            .find(|(_, c)| !is_wild(**c));
+        if let Some((depth, top)) = top {
+            if color(card) != self.color {
+                return Err(PlayError::WrongColor);
+            }
+            if gender(card) == (gender(*top) + depth as u8) % 2 {
+                return Err(PlayError::SameGender);
+            }
+            if number(card) as i64 != number(*top) as i64 - depth as i64 - 1 {
+                return Err(PlayError::WrongNumber);
+            }
+        }
+        Ok(())
+    }
+    fn add_post(&mut self, card: u8) -> Result<(), PlayError> {
+        self.verify_post(card)?;
+        if !is_wild(card) && self.cards.as_slice().iter().all(|c| is_wild(*c)) {
+            self.color = color(card);
+        }
+        self.cards.push(card)
+    }
+}
+
+impl CompactGame {
+    ///Copies the cards of `game`. Fails if the game has more players, wild cards, post piles or cards in a pile than fit.
+    pub fn from_game(game: &GameState) -> Result<CompactGame> {
+        let deck_size = game.cards_per_player();
+        if deck_size as usize > MAX_DECK_SIZE || game.players.len() > MAX_PLAYERS as usize {
+            return Err(anyhow!("Game is too large for a compact copy"));
+        }
+        let offset = |card: &u32| (card % deck_size) as u8;
+        let pile = |pile: &Pile| -> Result<CompactPile> {
+            Ok(CompactPile {
+                cards: Stack::from_cards(pile.cards.iter().map(offset))?,
+                color: pile.color,
+            })
+        };
+        let mut compact = CompactGame {
+            players: [CompactPlayer {
+                in_hand: Stack::new(),
+                available: Stack::new(),
+                blitz: Stack::new(),
+                post: [CompactPile::EMPTY; MAX_POST_PILES],
+                post_len: 0,
+                eliminated: false,
+            }; MAX_PLAYERS as usize],
+            player_count: game.players.len() as u8,
+            arena: [CompactArenaPile {
+                pile: CompactPile::EMPTY,
+                owners: [0; PILE_SIZE],
+            }; MAX_ARENA_PILES],
+            arena_len: 0,
+            deck_size: deck_size as u8,
+            draw_rate: game.draw_rate,
+            post_refill_rule: game.post_refill_rule,
+        };
+        for (player, state) in compact.players.iter_mut().zip(game.players.iter()) {
+            if state.post_pile.piles.len() > MAX_POST_PILES {
+                return Err(anyhow!("More than {MAX_POST_PILES} post piles"));
+            }
+            player.in_hand = Stack::from_cards(state.hand.in_hand.iter().map(offset))?;
+            player.available = Stack::from_cards(state.hand.available_to_play.iter().map(offset))?;
+            player.blitz = Stack::from_cards(state.blitz_pile.cards.iter().map(offset))?;
+            for (compact, post) in player.post.iter_mut().zip(state.post_pile.piles.iter()) {
+                *compact = pile(post)?;
+            }
+            player.post_len = state.post_pile.piles.len() as u8;
+            player.eliminated = state.is_eliminated();
+        }
+        if game.arena.piles.len() > MAX_ARENA_PILES {
+            return Err(anyhow!("More than {MAX_ARENA_PILES} arena piles"));
+        }
+        for (compact, arena) in compact.arena.iter_mut().zip(game.arena.piles.iter()) {
+            compact.pile = pile(arena)?;
+            for (owner, card) in compact.owners.iter_mut().zip(arena.cards.iter()) {
+                *owner = (card / deck_size) as u8;
+            }
+        }
+        compact.arena_len = game.arena.piles.len() as u8;
+        Ok(compact)
+    }
+    ///Writes the cards back into `game`, which should be the game this was copied from.
+    pub fn write_to(&self, game: &mut GameState) {
+        let deck_size = self.deck_size as u32;
+        let pile = |pile: &CompactPile, owner: &dyn Fn(usize) -> u32| Pile {
+            cards: pile
+                .cards
+                .as_slice()
+                .iter()
+                .enumerate()
+                .map(|(i, c)| owner(i) * deck_size + *c as u32)
+                .collect(),
+            color: pile.color,
+        };
+        for (i, (state, player)) in game.players.iter_mut().zip(self.players()).enumerate() {
+            let cards = |stack: &[u8]| {
+                stack
+                    .iter()
+                    .map(|c| i as u32 * deck_size + *c as u32)
+                    .collect()
+            };
+            state.hand.in_hand = cards(player.in_hand.as_slice());
+            state.hand.available_to_play = cards(player.available.as_slice());
+            state.blitz_pile.cards = cards(player.blitz.as_slice());
+            state.post_pile.piles = player
+                .post_piles()
+                .iter()
+                .map(|p| pile(p, &|_| i as u32))
+                .collect();
+        }
+        game.arena.piles = self
+            .arena_piles()
+            .iter()
+            .map(|p| pile(&p.pile, &|i| p.owners[i] as u32))
+            .collect();
+        game.draw_rate = self.draw_rate;
+    }
+
+    pub fn players(&self) -> &[CompactPlayer] {
+        &self.players[..self.player_count as usize]
+    }
+    pub fn arena_piles(&self) -> &[CompactArenaPile] {
+        &self.arena[..self.arena_len as usize]
+    }
+    pub fn can_call_blitz(&self, player: u32) -> bool {
+        self.players()
+            .get(player as usize)
+            .is_some_and(|p| !p.eliminated && p.blitz.is_empty())
+    }
+
+    ///Makes a play by the same rules as [`GameState::make_play`]. Rejected plays change nothing.
+    pub fn make_play(&mut self, play: Play) -> Result<(), PlayError> {
+        let player = play.player as usize;
+        let state = self.players().get(player).ok_or(PlayError::NoSuchPlayer)?;
+        if state.eliminated {
+            return Err(PlayError::Eliminated);
+        }
+        self.verify_post_refill_rule(play)?;
+        let state = &mut self.players[player];
+        match play.play {
+            Action::CallBlitz(_) => return Err(PlayError::CallBlitz),
+            Action::Arena(ArenaAction::FromAvailableHand(pile)) => {
+                let card = state.available.top().ok_or(PlayError::PileEmpty)?;
+                self.add_to_arena(player, pile, card)?;
+                self.players[player].available.pop();
+            }
+            Action::Arena(ArenaAction::FromBlitz(pile)) => {
+                let card = state.blitz.top().ok_or(PlayError::PileEmpty)?;
+                self.add_to_arena(player, pile, card)?;
+                self.players[player].blitz.pop();
+            }
+            Action::Arena(ArenaAction::FromPost {
+                post_pile,
+                arena_pile,
+            }) => {
+                let card = state.post_pile(post_pile)?;
+                let card = card.cards.top().ok_or(PlayError::PileEmpty)?;
+                self.add_to_arena(player, arena_pile, card)?;
+                self.players[player].post[post_pile as usize].cards.pop();
+            }
+            Action::Player(PlayerAction::BlitzToPost(pile)) => {
+                let card = state.blitz.top().ok_or(PlayError::PileEmpty)?;
+                state.post_pile(pile)?.add_post(card)?;
+                state.blitz.pop();
+            }
+            Action::Player(PlayerAction::AvailableToPost(pile)) => {
+                let card = state.available.top().ok_or(PlayError::PileEmpty)?;
+                state.post_pile(pile)?.add_post(card)?;
+                state.available.pop();
+            }
+            Action::Player(PlayerAction::PostToPost { from, to }) => {
+                if from == to {
+                    return Err(PlayError::SamePile);
+                }
+                let card = state
+                    .post_pile(from)?
+                    .cards
+                    .top()
+                    .ok_or(PlayError::PileEmpty)?;
+                state.post_pile(to)?.add_post(card)?;
+                state.post[from as usize].cards.pop();
+            }
+            Action::Player(PlayerAction::TransferToAvailable) => {
+                let count = (self.draw_rate as usize).min(state.in_hand.len());
+                if count == 0 {
+                    reset_hand(state);
+                } else {
+                    //the drawn cards keep their order
+                    let start = state.in_hand.len() - count;
+                    for i in start..state.in_hand.len() {
+                        let card = state.in_hand.cards[i];
+                        state.available.push(card)?;
+                    }
+                    state.in_hand.len = start as u8;
+                }
+            }
+            Action::Player(PlayerAction::ResetHand) => reset_hand(state),
+        }
+        if self.post_refill_rule == proto::PostRefillRule::AutomaticRefill {
+            let state = &mut self.players[player];
+            for pile in 0..state.post_len as usize {
+                if !state.post[pile].cards.is_empty() {
+                    continue;
+                }
+                let Some(card) = state.blitz.pop() else {
+                    break;
+                };
+                state.post[pile].add_post(card)?;
+            }
+        }
+        Ok(())
+    }
+    ///Follows [`crate::Arena::add_card`]: a 1 always starts a new pile, and anything else is added to `pile`.
+    fn add_to_arena(&mut self, player: usize, pile: u32, card: u8) -> Result<(), PlayError> {
+        if number(card) == 1 && !is_wild(card) {
+            let new_pile = self
+                .arena
+                .get_mut(self.arena_len as usize)
+                .ok_or(PlayError::PileFull)?;
+            *new_pile = CompactArenaPile {
+                pile: CompactPile {
+                    cards: Stack::new(),
+                    color: color(card),
+                },
+                owners: [0; PILE_SIZE],
+            };
+            new_pile.pile.cards.push(card)?;
+            new_pile.owners[0] = player as u8;
+            self.arena_len += 1;
+            return Ok(());
+        }
+        let arena = &mut self.arena[..self.arena_len as usize];
+        let arena_pile = arena.get_mut(pile as usize).ok_or(PlayError::NoSuchPile)?;
+        arena_pile.pile.verify_arena(card)?;
+        arena_pile.owners[arena_pile.pile.cards.len()] = player as u8;
+        arena_pile.pile.cards.push(card)
+    }
+    fn verify_post_refill_rule(&self, play: Play) -> Result<(), PlayError> {
+        if self.post_refill_rule != proto::PostRefillRule::ForcedRefill {
+            return Ok(());
+        }
+        let state = &self.players[play.player as usize];
+        let post = state.post_piles();
+        let needs_refill = !state.blitz.is_empty() && post.iter().any(|p| p.cards.is_empty());
+        match play.play {
+            Action::Player(PlayerAction::BlitzToPost(p))
+                if post.get(p as usize).is_some_and(|p| p.cards.is_empty()) =>
+            {
+                Ok(())
+            }
+            Action::Arena(_) | Action::Player(_) if needs_refill => Err(PlayError::RefillFirst),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn reset_hand(state: &mut CompactPlayer) {
+    for i in 0..state.available.len() {
+        let card = state.available.cards[i];
+        state.in_hand.cards[state.in_hand.len()] = card;
+        state.in_hand.len += 1;
+    }
+    state.available.len = 0;
+}
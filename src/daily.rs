@@ -0,0 +1,207 @@
+//!The daily challenge: a solo game that deals the same cards to everyone who plays it on the same day, and a leaderboard
+//! of each player's best run.
+//!
+//! Days are numbered from the Unix epoch, in UTC, so that the challenge changes at the same moment for every player.
+//! Runs are kept in a [`DailyStore`], so that the leaderboards outlive the server.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{proto, GameState};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+///The day a time falls on, counting from the Unix epoch.
+pub fn day_of(time: SystemTime) -> u32 {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    (seconds / SECONDS_PER_DAY) as u32
+}
+pub fn today() -> u32 {
+    day_of(SystemTime::now())
+}
+
+///The seed that deals a day's challenge. Neighbouring days get unrelated seeds, so that one deal says nothing about the next.
+pub fn seed(day: u32) -> u64 {
+    //splitmix64
+    let mut z = (day as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+///The rules every daily challenge is played with: the classic preset, played solo.
+pub fn prefs() -> proto::GamePrefs {
+    proto::GamePrefs {
+        game_mode: proto::GameMode::Solo as i32,
+        ..crate::presets::get("classic").unwrap().prefs()
+    }
+}
+
+///Deals a day's challenge.
+pub fn new_game(day: u32) -> Result<GameState> {
+    GameState::new_seeded(1, prefs(), seed(day))
+}
+
+///Orders runs from best to worst: completed runs first, then the fastest, then the one with the fewest moves.
+pub fn compare_runs(a: &proto::SoloResult, b: &proto::SoloResult) -> Ordering {
+    b.completed
+        .cmp(&a.completed)
+        .then(a.completion_ms.cmp(&b.completion_ms))
+        .then(a.moves.cmp(&b.moves))
+}
+
+///Keeps every daily challenge run.
+pub trait DailyStore: Send + Sync {
+    ///Adds a run. Runs are never changed or removed.
+    fn append(&self, day: u32, username: &str, result: &proto::SoloResult) -> Result<()>;
+    ///Every run added so far, oldest first.
+    fn load(&self) -> Result<Vec<(u32, String, proto::SoloResult)>>;
+}
+
+///A [`DailyStore`] that appends each run to a file, as a line of JSON.
+pub struct FileStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredRun {
+    day: u32,
+    username: String,
+    completed: bool,
+    completion_ms: u32,
+    moves: u32,
+}
+impl FileStore {
+    ///Opens the file at `path`, creating it if it does not exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<FileStore> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Could not open {}", path.display()))?;
+        Ok(FileStore {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+}
+impl DailyStore for FileStore {
+    fn append(&self, day: u32, username: &str, result: &proto::SoloResult) -> Result<()> {
+        let mut line = serde_json::to_string(&StoredRun {
+            day,
+            username: username.to_string(),
+            completed: result.completed,
+            completion_ms: result.completion_ms,
+            moves: result.moves,
+        })?;
+        line.push('\n');
+        //a single write, so that runs recorded at the same time do not interleave
+        self.file
+            .lock()
+            .map_err(|_| anyhow!("Daily results file lock poisoned"))?
+            .write_all(line.as_bytes())
+            .with_context(|| format!("Could not write to {}", self.path.display()))
+    }
+    ///Lines that cannot be read, such as one cut short when the server stopped, are skipped.
+    fn load(&self) -> Result<Vec<(u32, String, proto::SoloResult)>> {
+        let contents = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Could not read {}", self.path.display()))?;
+        let runs = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(i, line)| match serde_json::from_str::<StoredRun>(line) {
+                Ok(run) => Some((
+                    run.day,
+                    run.username,
+                    proto::SoloResult {
+                        completed: run.completed,
+                        completion_ms: run.completion_ms,
+                        moves: run.moves,
+                    },
+                )),
+                Err(e) => {
+                    tracing::warn!("Skipping line {} of {}: {e}", i + 1, self.path.display());
+                    None
+                }
+            })
+            .collect();
+        Ok(runs)
+    }
+}
+
+///The best run of each player, for every day a challenge was played.
+#[derive(Default)]
+pub struct DailyResults {
+    days: DashMap<u32, HashMap<String, proto::SoloResult>>,
+    store: Option<Box<dyn DailyStore>>,
+}
+
+impl DailyResults {
+    ///Results that are only kept in memory, and are lost when the server stops.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///Results kept in `store`, starting with the runs already in it.
+    pub fn with_store(store: impl DailyStore + 'static) -> Result<Self> {
+        let results = Self::default();
+        for (day, username, result) in store.load()? {
+            results.keep_best(day, &username, result);
+        }
+        Ok(DailyResults {
+            store: Some(Box::new(store)),
+            ..results
+        })
+    }
+    ///Records a run, adding it to the store if there is one. Only the player's best run of the day is on the leaderboard.
+    pub fn record(&self, day: u32, username: &str, result: proto::SoloResult) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.append(day, username, &result)?;
+        }
+        self.keep_best(day, username, result);
+        Ok(())
+    }
+    fn keep_best(&self, day: u32, username: &str, result: proto::SoloResult) {
+        let mut runs = self.days.entry(day).or_default();
+        match runs.get_mut(username) {
+            Some(best) if compare_runs(&result, best).is_lt() => *best = result,
+            Some(_) => {}
+            None => {
+                runs.insert(username.to_string(), result);
+            }
+        }
+    }
+    ///Every player's best run of the day, best first. Players with equal runs are listed by username.
+    pub fn leaderboard(&self, day: u32) -> proto::DailyLeaderboard {
+        let mut runs: Vec<(String, proto::SoloResult)> = self
+            .days
+            .get(&day)
+            .map(|runs| runs.iter().map(|(u, r)| (u.clone(), r.clone())).collect())
+            .unwrap_or_default();
+        runs.sort_by(|(a_name, a), (b_name, b)| {
+            compare_runs(a, b).then_with(|| a_name.cmp(b_name))
+        });
+        let results = runs
+            .into_iter()
+            .map(|(username, result)| proto::DailyResult {
+                username,
+                result: Some(result),
+            })
+            .collect();
+        proto::DailyLeaderboard { day, results }
+    }
+}
@@ -0,0 +1,252 @@
+//!A C ABI over [`GameState`], built with the `ffi` feature, so that game clients can run the same rules locally for
+//! client-side prediction and offline play. The declarations are in `include/blitz.h`, and the `blitz-ffi` crate in `ffi/`
+//! builds the C library.
+//!
+//! Prefs, plays and snapshots are passed as protobuf encoded [`proto::GamePrefs`], [`proto::Play`] and [`proto::GameSnapshot`]
+//! messages, the same ones the server uses. Functions that can fail return [`BLITZ_ERROR`] (or null), and
+//! [`blitz_last_error`] describes the failure. Functions that fill a buffer return the size they need, and only write to the
+//! buffer if it is large enough.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use anyhow::{anyhow, Result};
+use prost::Message;
+
+use crate::{proto, GameState};
+
+pub const BLITZ_ERROR: i64 = -1;
+
+///The pile kinds read by [`blitz_game_pile`] and counted by [`blitz_game_pile_count`].
+pub const BLITZ_PILE_HAND: u32 = 0;
+pub const BLITZ_PILE_AVAILABLE: u32 = 1;
+pub const BLITZ_PILE_BLITZ: u32 = 2;
+pub const BLITZ_PILE_POST: u32 = 3;
+pub const BLITZ_PILE_ARENA: u32 = 4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+///Runs `f`, turning errors and panics into [`BLITZ_ERROR`], since neither may cross into C.
+fn guard(f: impl FnOnce() -> Result<i64>) -> i64 {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(anyhow!("Internal error in the game engine")));
+    result.unwrap_or_else(|e| {
+        let message = CString::new(format!("{e:#}").replace('\0', "")).unwrap_or_default();
+        LAST_ERROR.with(|last| *last.borrow_mut() = message);
+        BLITZ_ERROR
+    })
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(anyhow!("Input is null")),
+        (false, _) => Ok(std::slice::from_raw_parts(data, len)),
+    }
+}
+
+unsafe fn game<'a>(game: *mut GameState) -> Result<&'a mut GameState> {
+    game.as_mut().ok_or_else(|| anyhow!("Game is null"))
+}
+
+///Copies `data` into `out` if it fits. Returns the length of `data`.
+unsafe fn output<T: Copy>(data: &[T], out: *mut T, capacity: usize) -> Result<i64> {
+    if data.len() <= capacity && !data.is_empty() {
+        if out.is_null() {
+            return Err(anyhow!("Output buffer is null"));
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    }
+    Ok(data.len() as i64)
+}
+
+///Describes the last failure on the calling thread. The string stays valid until the next failure on the same thread.
+#[no_mangle]
+pub extern "C" fn blitz_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+///Creates a game from protobuf encoded prefs, dealt the same way for the same seed. Returns null on failure.
+/// The game must be freed with [`blitz_game_free`].
+///
+/// # Safety
+/// `prefs` must point to `prefs_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_new(
+    prefs: *const u8,
+    prefs_len: usize,
+    player_count: u32,
+    seed: u64,
+) -> *mut GameState {
+    let mut created = std::ptr::null_mut();
+    guard(|| {
+        let prefs = proto::GamePrefs::decode(input(prefs, prefs_len)?)?;
+        let game = GameState::new_seeded(player_count, prefs, seed)?;
+        created = Box::into_raw(Box::new(game));
+        Ok(0)
+    });
+    created
+}
+
+///Frees a game created by [`blitz_game_new`]. Does nothing if `game` is null.
+///
+/// # Safety
+/// `game` must be null or a game from [`blitz_game_new`] that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_free(game: *mut GameState) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+///Makes a protobuf encoded play. Returns 0, or [`BLITZ_ERROR`] if the play is not allowed, in which case nothing changes.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`], and `play` must point to `play_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_make_play(
+    game: *mut GameState,
+    play: *const u8,
+    play_len: usize,
+) -> i64 {
+    guard(|| {
+        let play: crate::Play = proto::Play::decode(input(play, play_len)?)?.try_into()?;
+        self::game(game)?.make_play(play)?;
+        Ok(0)
+    })
+}
+
+///Returns 1 if the round should end, otherwise 0.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`].
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_should_end_round(game: *mut GameState) -> i64 {
+    guard(|| Ok(self::game(game)?.should_end_round() as i64))
+}
+
+///Scores the round and, unless the game is over, deals the next one. Returns 0.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`].
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_end_round(game: *mut GameState) -> i64 {
+    guard(|| {
+        self::game(game)?.end_round()?;
+        Ok(0)
+    })
+}
+
+///Returns 1 if the game is over, otherwise 0.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`].
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_is_game_over(game: *mut GameState) -> i64 {
+    guard(|| Ok(self::game(game)?.is_game_over() as i64))
+}
+
+///The number of piles of a kind: the player's post piles, the arena piles, or 1 for the other kinds.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`].
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_pile_count(
+    game: *mut GameState,
+    player: u32,
+    kind: u32,
+) -> i64 {
+    guard(|| {
+        let game = self::game(game)?;
+        if kind == BLITZ_PILE_ARENA {
+            return Ok(game.arena.piles.len() as i64);
+        }
+        let player = game
+            .players
+            .get(player as usize)
+            .ok_or_else(|| anyhow!("Player {player} does not exist"))?;
+        Ok(match kind {
+            BLITZ_PILE_POST => player.post_pile.piles.len() as i64,
+            BLITZ_PILE_HAND | BLITZ_PILE_AVAILABLE | BLITZ_PILE_BLITZ => 1,
+            _ => return Err(anyhow!("Unknown pile kind {kind}")),
+        })
+    })
+}
+
+///Writes the cards of a pile, from the bottom to the top, as indices into the deck. `player` is ignored for arena piles,
+/// and `index` is only used for post and arena piles. Returns the number of cards in the pile.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`], and `out` must point to room for `capacity` cards.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_pile(
+    game: *mut GameState,
+    player: u32,
+    kind: u32,
+    index: u32,
+    out: *mut u32,
+    capacity: usize,
+) -> i64 {
+    guard(|| {
+        let game = self::game(game)?;
+        let pile_missing = || anyhow!("Pile {index} does not exist");
+        if kind == BLITZ_PILE_ARENA {
+            let pile = game
+                .arena
+                .piles
+                .get(index as usize)
+                .ok_or_else(pile_missing)?;
+            return output(&pile.cards, out, capacity);
+        }
+        let player = game
+            .players
+            .get(player as usize)
+            .ok_or_else(|| anyhow!("Player {player} does not exist"))?;
+        let cards = match kind {
+            BLITZ_PILE_HAND => &player.hand.in_hand,
+            BLITZ_PILE_AVAILABLE => &player.hand.available_to_play,
+            BLITZ_PILE_BLITZ => &player.blitz_pile.cards,
+            BLITZ_PILE_POST => {
+                let pile = player.post_pile.piles.get(index as usize);
+                &pile.ok_or_else(pile_missing)?.cards
+            }
+            _ => return Err(anyhow!("Unknown pile kind {kind}")),
+        };
+        output(cards, out, capacity)
+    })
+}
+
+///Writes the protobuf encoded [`proto::GlobalDeck`], which describes the card behind every index. Returns its size.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`], and `out` must point to `capacity` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_deck(
+    game: *mut GameState,
+    out: *mut u8,
+    capacity: usize,
+) -> i64 {
+    guard(|| {
+        output(
+            &self::game(game)?.global_deck().encode_to_vec(),
+            out,
+            capacity,
+        )
+    })
+}
+
+///Writes the protobuf encoded [`proto::GameSnapshot`] of the game. Returns its size.
+///
+/// # Safety
+/// `game` must be a live game from [`blitz_game_new`], and `out` must point to `capacity` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blitz_game_snapshot(
+    game: *mut GameState,
+    out: *mut u8,
+    capacity: usize,
+) -> i64 {
+    guard(|| output(&self::game(game)?.snapshot().encode_to_vec(), out, capacity))
+}
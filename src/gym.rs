@@ -0,0 +1,255 @@
+//!A gym-style environment for training agents, with a fixed-size action space and a numeric observation of one player's view.
+//!
+//! Every player acts through [`BlitzEnv::step`], in whatever order the caller chooses. Rounds end as they would on the server:
+//! when someone calls blitz, or when [`GameState::should_end_round`] says so. An episode lasts until the game is over.
+//!
+//! Actions are numbered as follows, where `A` is the most arena piles there can be (four per player) and `P` is the number of post piles:
+//! | Index                     | Action                                    |
+//! |---------------------------|-------------------------------------------|
+//! | `0`                       | [`Action::CallBlitz`]                     |
+//! | `1`                       | [`PlayerAction::TransferToAvailable`]     |
+//! | `2`                       | [`PlayerAction::ResetHand`]               |
+//! | `3 + pile`                | [`ArenaAction::FromAvailableHand`]        |
+//! | `3 + A + pile`            | [`ArenaAction::FromBlitz`]                |
+//! | `3 + 2A + post * A + pile`| [`ArenaAction::FromPost`]                 |
+//! | then `+ post`             | [`PlayerAction::BlitzToPost`]             |
+//! | then `+ post`             | [`PlayerAction::AvailableToPost`]         |
+//! | then `+ from * P + to`    | [`PlayerAction::PostToPost`]              |
+
+use anyhow::{anyhow, Result};
+
+use crate::{proto, Action, ArenaAction, Card, GameState, Play, PlayerAction};
+
+///How many numbers describe a card in an observation: whether there is one, its color, its number and whether it is wild.
+const CARD_FEATURES: usize = 7;
+
+pub struct BlitzEnv {
+    pub game: GameState,
+    player_count: u32,
+    prefs: proto::GamePrefs,
+    ///Each player's score when they were last rewarded.
+    rewarded: Vec<i32>,
+}
+
+///The result of one step.
+#[derive(Clone, Debug)]
+pub struct Step {
+    ///What the acting player sees after the step.
+    pub observation: Vec<f32>,
+    ///The change in the acting player's score since their last step, counting the cards they have in the arena and in their
+    /// blitz pile in the round in progress. Points gained or lost through other players' moves are included in the next step.
+    pub reward: f32,
+    ///Whether the game is over.
+    pub done: bool,
+}
+
+impl BlitzEnv {
+    ///Creates an environment for games with the given prefs. Fails with [`crate::prefs::InvalidPrefs`] if they are not valid.
+    pub fn new(player_count: u32, prefs: proto::GamePrefs) -> Result<BlitzEnv> {
+        let game = GameState::new_seeded(player_count, prefs.clone(), 0)?;
+        Ok(BlitzEnv {
+            game,
+            player_count,
+            prefs,
+            rewarded: vec![0; player_count as usize],
+        })
+    }
+    ///Starts a new game, dealt the same way for the same seed. Returns what each player sees.
+    pub fn reset(&mut self, seed: u64) -> Result<Vec<Vec<f32>>> {
+        self.game = GameState::new_seeded(self.player_count, self.prefs.clone(), seed)?;
+        self.rewarded = vec![0; self.player_count as usize];
+        Ok((0..self.player_count).map(|p| self.observe(p)).collect())
+    }
+    ///Makes the play numbered `action` for `player`. Fails if the number is out of range, or the game rejects the play,
+    /// in which case nothing changes. [`BlitzEnv::action_mask`] tells which plays will be accepted.
+    pub fn step(&mut self, player: u32, action: usize) -> Result<Step> {
+        if player >= self.player_count {
+            return Err(anyhow!("Player {player} does not exist"));
+        }
+        if self.game.is_game_over() {
+            return Err(anyhow!("Game is over"));
+        }
+        let play = self
+            .action(action)
+            .ok_or_else(|| anyhow!("Action {action} is out of range"))?;
+        let play = match play {
+            Action::CallBlitz(_) => Action::CallBlitz(player),
+            play => play,
+        };
+        self.game.make_play(Play { player, play })?;
+        if !self.game.is_game_over() && self.game.should_end_round() {
+            self.game.end_round()?;
+        }
+        let score = self.score(player);
+        let reward = score - std::mem::replace(&mut self.rewarded[player as usize], score);
+        Ok(Step {
+            observation: self.observe(player),
+            reward: reward as f32,
+            done: self.game.is_game_over(),
+        })
+    }
+
+    ///The player's total score, plus their points so far in the round in progress.
+    fn score(&self, player: u32) -> i32 {
+        let live = if self.game.is_game_over() {
+            0
+        } else {
+            self.game.round_points()[player as usize]
+        };
+        self.game.scoreboard.get_totals()[player as usize] + live
+    }
+    fn arena_slots(&self) -> usize {
+        4 * self.player_count as usize
+    }
+    fn post_slots(&self) -> usize {
+        self.prefs.post_pile_size as usize
+    }
+
+    ///The number of actions, which is the same for every game with these prefs and player count.
+    pub fn action_count(&self) -> usize {
+        let (a, p) = (self.arena_slots(), self.post_slots());
+        3 + 2 * a + p * a + 2 * p + p * p
+    }
+    ///The play an action number stands for. [`Action::CallBlitz`] is filled in with the acting player by [`BlitzEnv::step`].
+    pub fn action(&self, mut index: usize) -> Option<Action> {
+        let (a, p) = (self.arena_slots(), self.post_slots());
+        let mut next = |count: usize| {
+            if index < count {
+                Some(index as u32)
+            } else {
+                index -= count;
+                None
+            }
+        };
+        if next(1).is_some() {
+            return Some(Action::CallBlitz(0));
+        }
+        if next(1).is_some() {
+            return Some(Action::Player(PlayerAction::TransferToAvailable));
+        }
+        if next(1).is_some() {
+            return Some(Action::Player(PlayerAction::ResetHand));
+        }
+        if let Some(pile) = next(a) {
+            return Some(Action::Arena(ArenaAction::FromAvailableHand(pile)));
+        }
+        if let Some(pile) = next(a) {
+            return Some(Action::Arena(ArenaAction::FromBlitz(pile)));
+        }
+        if let Some(i) = next(p * a) {
+            return Some(Action::Arena(ArenaAction::FromPost {
+                post_pile: i / a as u32,
+                arena_pile: i % a as u32,
+            }));
+        }
+        if let Some(post) = next(p) {
+            return Some(Action::Player(PlayerAction::BlitzToPost(post)));
+        }
+        if let Some(post) = next(p) {
+            return Some(Action::Player(PlayerAction::AvailableToPost(post)));
+        }
+        let i = next(p * p)?;
+        Some(Action::Player(PlayerAction::PostToPost {
+            from: i / p as u32,
+            to: i % p as u32,
+        }))
+    }
+    ///The number of a play, or `None` if it names a pile outside the action space.
+    pub fn action_index(&self, play: Action) -> Option<usize> {
+        let (a, p) = (self.arena_slots(), self.post_slots());
+        let slot = |pile: u32, count: usize| ((pile as usize) < count).then_some(pile as usize);
+        //the first index of each group of actions
+        let from_post = 3 + 2 * a;
+        let blitz_to_post = from_post + p * a;
+        let available_to_post = blitz_to_post + p;
+        let post_to_post = available_to_post + p;
+        match play {
+            Action::CallBlitz(_) => Some(0),
+            Action::Player(PlayerAction::TransferToAvailable) => Some(1),
+            Action::Player(PlayerAction::ResetHand) => Some(2),
+            Action::Arena(ArenaAction::FromAvailableHand(pile)) => Some(3 + slot(pile, a)?),
+            Action::Arena(ArenaAction::FromBlitz(pile)) => Some(3 + a + slot(pile, a)?),
+            Action::Arena(ArenaAction::FromPost {
+                post_pile,
+                arena_pile,
+            }) => Some(from_post + slot(post_pile, p)? * a + slot(arena_pile, a)?),
+            Action::Player(PlayerAction::BlitzToPost(post)) => Some(blitz_to_post + slot(post, p)?),
+            Action::Player(PlayerAction::AvailableToPost(post)) => {
+                Some(available_to_post + slot(post, p)?)
+            }
+            Action::Player(PlayerAction::PostToPost { from, to }) => {
+                Some(post_to_post + slot(from, p)? * p + slot(to, p)?)
+            }
+        }
+    }
+    ///Which actions the game would accept from the player right now.
+    pub fn action_mask(&self, player: u32) -> Vec<bool> {
+        let mut mask = vec![false; self.action_count()];
+        if self.game.is_game_over() {
+            return mask;
+        }
+        for play in self.game.legal_moves(player) {
+            if let Some(i) = self.action_index(play) {
+                mask[i] = true;
+            }
+        }
+        mask
+    }
+
+    ///The number of values in an observation.
+    pub fn observation_size(&self) -> usize {
+        let p = self.post_slots();
+        let own = 3 + 2 * CARD_FEATURES + p * CARD_FEATURES;
+        let opponent = 1 + 2 * CARD_FEATURES + p * CARD_FEATURES;
+        own + self.arena_slots() * CARD_FEATURES + (self.player_count as usize - 1) * opponent
+    }
+    ///What the player can see: their own piles, the top of every arena pile, then the face up cards of each other player,
+    /// starting with the next seat. Counts are scaled to be roughly between 0 and 1.
+    pub fn observe(&self, player: u32) -> Vec<f32> {
+        let game = &self.game;
+        let mut observation = Vec::with_capacity(self.observation_size());
+        let top = |cards: &[u32]| cards.last().map(|c| game.card_context.cards[*c as usize]);
+        let post_tops = |observation: &mut Vec<f32>, player: usize| {
+            for i in 0..self.post_slots() {
+                let pile = game.players[player].post_pile.piles.get(i);
+                push_card(observation, pile.and_then(|p| top(&p.cards)));
+            }
+        };
+
+        let own = &game.players[player as usize];
+        observation.push(own.hand.count_in_hand() as f32 / 40.0);
+        observation.push(own.hand.count_available() as f32 / 40.0);
+        observation.push(own.blitz_pile.cards.len() as f32 / 10.0);
+        push_card(&mut observation, top(&own.hand.available_to_play));
+        push_card(&mut observation, top(&own.blitz_pile.cards));
+        post_tops(&mut observation, player as usize);
+
+        for i in 0..self.arena_slots() {
+            let pile = game.arena.piles.get(i);
+            push_card(&mut observation, pile.and_then(|p| top(&p.cards)));
+        }
+
+        for offset in 1..self.player_count {
+            let other = ((player + offset) % self.player_count) as usize;
+            let state = &game.players[other];
+            observation.push(state.blitz_pile.cards.len() as f32 / 10.0);
+            push_card(&mut observation, top(&state.hand.available_to_play));
+            push_card(&mut observation, top(&state.blitz_pile.cards));
+            post_tops(&mut observation, other);
+        }
+        observation
+    }
+}
+
+fn push_card(observation: &mut Vec<f32>, card: Option<Card>) {
+    let Some(card) = card else {
+        observation.extend([0.0; CARD_FEATURES]);
+        return;
+    };
+    let mut colors = [0.0; 4];
+    colors[card.color as usize] = 1.0;
+    observation.push(1.0);
+    observation.extend(colors);
+    observation.push(card.number as f32 / 10.0);
+    observation.push(if card.is_wild() { 1.0 } else { 0.0 });
+}
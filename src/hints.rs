@@ -0,0 +1,159 @@
+//!Finds the legal moves of a player and ranks them, to point new players at the moves they are missing.
+
+use crate::{proto, Action, ArenaAction, Card, GameState, Play, PlayerAction};
+
+///A legal move, along with how good it looks.
+#[derive(Clone, Copy, Debug)]
+pub struct Hint {
+    pub play: Action,
+    ///Higher is better.
+    pub score: i32,
+    pub reason: &'static str,
+}
+
+impl GameState {
+    ///Every move the player could make right now. Moves that only differ in which arena pile a 1 would start are listed once.
+    pub fn legal_moves(&self, player: u32) -> Vec<Action> {
+        let can_call_blitz = if self.game_mode == proto::GameMode::Cooperative {
+            self.cooperative_goal_reached()
+        } else {
+            self.players
+                .get(player as usize)
+                .is_some_and(|p| p.can_call_blitz())
+        };
+        self.candidate_moves(player)
+            .into_iter()
+            .filter(|play| match play {
+                Action::CallBlitz(_) => can_call_blitz,
+                _ => self
+                    .clone()
+                    .make_play(Play {
+                        player,
+                        play: *play,
+                    })
+                    .is_ok(),
+            })
+            .collect()
+    }
+    ///The moves worth trying for the player, some of which may be rejected by [`GameState::make_play`].
+    pub(crate) fn candidate_moves(&self, player: u32) -> Vec<Action> {
+        let Some(state) = self.players.get(player as usize) else {
+            return vec![];
+        };
+        let post_piles = state.post_pile.piles.len() as u32;
+        let top = |cards: &[u32]| cards.last().map(|c| self.card_context.cards[*c as usize]);
+        //a 1 starts a new pile wherever it is played, so only one arena pile needs to be tried
+        let arena_piles = |card: Option<Card>| match card {
+            Some(c) if c.number == 1 && !c.is_wild() => vec![self.arena.piles.len() as u32],
+            _ => (0..self.arena.piles.len() as u32).collect(),
+        };
+
+        let mut candidates = vec![Action::CallBlitz(player)];
+        for pile in arena_piles(top(&state.hand.available_to_play)) {
+            candidates.push(Action::Arena(ArenaAction::FromAvailableHand(pile)));
+        }
+        for pile in arena_piles(top(&state.blitz_pile.cards)) {
+            candidates.push(Action::Arena(ArenaAction::FromBlitz(pile)));
+        }
+        for (post_pile, post) in state.post_pile.piles.iter().enumerate() {
+            for arena_pile in arena_piles(top(&post.cards)) {
+                candidates.push(Action::Arena(ArenaAction::FromPost {
+                    post_pile: post_pile as u32,
+                    arena_pile,
+                }));
+            }
+        }
+        candidates.push(Action::Player(PlayerAction::TransferToAvailable));
+        candidates.push(Action::Player(PlayerAction::ResetHand));
+        for to in 0..post_piles {
+            candidates.push(Action::Player(PlayerAction::BlitzToPost(to)));
+            candidates.push(Action::Player(PlayerAction::AvailableToPost(to)));
+            for from in (0..post_piles).filter(|from| *from != to) {
+                candidates.push(Action::Player(PlayerAction::PostToPost { from, to }));
+            }
+        }
+        candidates
+    }
+
+    ///The player's legal moves, best first. Getting rid of blitz cards matters most, then scoring arena points,
+    /// then anything that uncovers more cards. Shuffling cards between post piles and cycling the hand come last.
+    pub fn hint(&self, player: u32) -> Vec<Hint> {
+        let Some(state) = self.players.get(player as usize) else {
+            return vec![];
+        };
+        let completes_pile = |pile: u32| {
+            self.arena
+                .piles
+                .get(pile as usize)
+                .is_some_and(|p| p.cards.len() == 9)
+        };
+        let empties_post = |pile: u32| {
+            state
+                .post_pile
+                .piles
+                .get(pile as usize)
+                .is_some_and(|p| p.cards.len() == 1)
+                && !state.blitz_pile.cards.is_empty()
+        };
+        let mut hints: Vec<Hint> = self
+            .legal_moves(player)
+            .into_iter()
+            .map(|play| {
+                let (score, reason) = match play {
+                    Action::CallBlitz(_) => (100, "Your blitz pile is empty, call blitz"),
+                    Action::Arena(ArenaAction::FromBlitz(_)) => {
+                        (50, "Play your blitz card into the arena")
+                    }
+                    Action::Arena(ArenaAction::FromPost { post_pile, .. })
+                        if empties_post(post_pile) =>
+                    {
+                        (
+                            45,
+                            "Play a post card into the arena to make room for a blitz card",
+                        )
+                    }
+                    Action::Arena(ArenaAction::FromPost { .. }) => {
+                        (40, "Play a post card into the arena")
+                    }
+                    Action::Player(PlayerAction::BlitzToPost(_)) => {
+                        (35, "Move your blitz card onto a post pile")
+                    }
+                    Action::Arena(ArenaAction::FromAvailableHand(_)) => {
+                        (30, "Play your available card into the arena")
+                    }
+                    Action::Player(PlayerAction::AvailableToPost(_)) => {
+                        (15, "Stack your available card on a post pile")
+                    }
+                    Action::Player(PlayerAction::PostToPost { from, .. }) if empties_post(from) => {
+                        (12, "Combine post piles to make room for a blitz card")
+                    }
+                    Action::Player(PlayerAction::PostToPost { .. }) => {
+                        (2, "Move a card between post piles")
+                    }
+                    Action::Player(PlayerAction::TransferToAvailable) => {
+                        (5, "Draw more cards from your hand")
+                    }
+                    Action::Player(PlayerAction::ResetHand) => (1, "Start over with your hand"),
+                };
+                let bonus = match play {
+                    Action::Arena(
+                        ArenaAction::FromBlitz(pile)
+                        | ArenaAction::FromAvailableHand(pile)
+                        | ArenaAction::FromPost {
+                            arena_pile: pile, ..
+                        },
+                    ) if completes_pile(pile) => 5,
+                    _ => 0,
+                };
+                Hint {
+                    play,
+                    score: score + bonus,
+                    reason,
+                }
+            })
+            .collect();
+        //the sort is stable, so equally good moves stay in the order they were found
+        hints.sort_by_key(|h| std::cmp::Reverse(h.score));
+        hints
+    }
+}
@@ -1,10 +1,29 @@
 pub mod server;
 
+pub mod analysis;
+pub mod bot;
+pub mod compact;
+pub mod daily;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gym;
+pub mod hints;
+pub mod prefs;
+pub mod presets;
 pub mod proto;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod scenario;
+pub mod solver;
+pub mod tables;
 mod test;
 use anyhow::{anyhow, Context, Result};
 use proto::{ArenaStateChange, GameStateChange, PlayerStateChange, ServerGameStateAction};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 ///Represents a card in the game. It is very similar to normal playing cards, with some differences.
 /// Each card can have a number 1-10, a color, and a gender (boy or girl), and an id (which is associated with the 'face'/image in the original game (and in the client)).
@@ -15,17 +34,21 @@ pub struct Card {
     pub number: u32,
     pub color: Color,
     pub gender: Gender,
+    pub kind: CardKind,
+}
+impl Card {
+    pub fn is_wild(&self) -> bool {
+        self.kind == CardKind::Wild
+    }
 }
 
 ///Shuffle cards in place
-pub fn shuffle(cards: &mut Vec<u32>) -> &mut Vec<u32> {
-    let mut rng = rand::thread_rng();
-    let cards = cards;
-    cards.shuffle(&mut rng);
+pub fn shuffle<'a, R: Rng>(cards: &'a mut Vec<u32>, rng: &mut R) -> &'a mut Vec<u32> {
+    cards.shuffle(rng);
     cards
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Action {
     Arena(ArenaAction),
     Player(PlayerAction),
@@ -33,7 +56,7 @@ pub enum Action {
     /// the player on which blitz was called loses 10 points.
     CallBlitz(u32),
 }
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 ///Plays that transfer cards from a player's hand to the arena.
 pub enum ArenaAction {
     FromAvailableHand(u32),
@@ -47,12 +70,17 @@ pub enum ArenaAction {
 }
 
 ///Plays that modify the players own cards
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PlayerAction {
     BlitzToPost(u32),
     AvailableToPost(u32),
     TransferToAvailable,
     ResetHand,
+    ///Move the top card of one post pile onto another
+    PostToPost {
+        from: u32,
+        to: u32,
+    },
 }
 ///Represents the types of plays that can be made by a player.
 #[derive(Clone, Copy, Debug)]
@@ -61,24 +89,50 @@ pub struct Play {
     pub play: Action,
 }
 ///represents a player in the game.
+#[derive(Clone)]
 pub struct Player {
     pub player_id: u32,
     pub hand: PlayerHand,
     pub post_pile: PostPile,
     pub blitz_pile: BlitzPile,
+    ///The round in which the player was eliminated. Eliminated players keep their seat as spectators, with no cards.
+    pub eliminated_in_round: Option<u32>,
 }
 impl Player {
     pub fn can_call_blitz(&self) -> bool {
-        self.blitz_pile.can_call_blitz()
+        !self.is_eliminated() && self.blitz_pile.can_call_blitz()
+    }
+    pub fn is_eliminated(&self) -> bool {
+        self.eliminated_in_round.is_some()
     }
 }
 
+#[derive(Clone)]
 pub struct GameStateBuilder {
     pub draw_rate: u32,
     pub post_pile_size: u32,
     pub player_count: u32,
     pub score_to_win: u32,
     pub blitz_deduction: u32,
+    pub auto_end_round: bool,
+    pub post_refill_rule: proto::PostRefillRule,
+    pub misplay_penalty: u32,
+    pub misplay_lockout: Duration,
+    pub draw_rate_schedule: Vec<proto::DrawRateStep>,
+    pub wild_cards_per_player: u32,
+    pub wild_card_bonus: i32,
+    pub arena_card_points: i32,
+    pub blitz_card_points: i32,
+    pub completed_pile_bonus: i32,
+    pub blitz_bonus: i32,
+    pub win_condition: proto::WinCondition,
+    pub round_limit: u32,
+    pub round_wins_to_win: u32,
+    pub time_limit: Duration,
+    pub game_mode: proto::GameMode,
+    pub cooperative_arena_goal: u32,
+    ///When set, every deal of the game is the same for the same seed.
+    pub seed: Option<u64>,
 }
 impl GameStateBuilder {
     pub fn new() -> Self {
@@ -88,6 +142,24 @@ impl GameStateBuilder {
             player_count: 2,
             score_to_win: 72,
             blitz_deduction: 10,
+            auto_end_round: false,
+            post_refill_rule: proto::PostRefillRule::ManualRefill,
+            misplay_penalty: 0,
+            misplay_lockout: Duration::ZERO,
+            draw_rate_schedule: vec![],
+            wild_cards_per_player: 0,
+            wild_card_bonus: 0,
+            arena_card_points: 1,
+            blitz_card_points: -2,
+            completed_pile_bonus: 0,
+            blitz_bonus: 0,
+            win_condition: proto::WinCondition::ScoreToWin,
+            round_limit: 0,
+            round_wins_to_win: 0,
+            time_limit: Duration::ZERO,
+            game_mode: proto::GameMode::Classic,
+            cooperative_arena_goal: 0,
+            seed: None,
         }
     }
     pub fn with_draw_rate(mut self, draw_rate: u32) -> Self {
@@ -110,9 +182,107 @@ impl GameStateBuilder {
         self.blitz_deduction = blitz_deduction;
         self
     }
+    pub fn with_auto_end_round(mut self, auto_end_round: bool) -> Self {
+        self.auto_end_round = auto_end_round;
+        self
+    }
+    pub fn with_post_refill_rule(mut self, post_refill_rule: proto::PostRefillRule) -> Self {
+        self.post_refill_rule = post_refill_rule;
+        self
+    }
+    pub fn with_misplay_penalty(mut self, misplay_penalty: u32) -> Self {
+        self.misplay_penalty = misplay_penalty;
+        self
+    }
+    pub fn with_misplay_lockout(mut self, misplay_lockout: Duration) -> Self {
+        self.misplay_lockout = misplay_lockout;
+        self
+    }
+    pub fn with_draw_rate_schedule(mut self, draw_rate_schedule: Vec<proto::DrawRateStep>) -> Self {
+        self.draw_rate_schedule = draw_rate_schedule;
+        self
+    }
+    pub fn with_wild_cards_per_player(mut self, wild_cards_per_player: u32) -> Self {
+        self.wild_cards_per_player = wild_cards_per_player;
+        self
+    }
+    pub fn with_wild_card_bonus(mut self, wild_card_bonus: i32) -> Self {
+        self.wild_card_bonus = wild_card_bonus;
+        self
+    }
+    pub fn with_arena_card_points(mut self, arena_card_points: i32) -> Self {
+        self.arena_card_points = arena_card_points;
+        self
+    }
+    pub fn with_blitz_card_points(mut self, blitz_card_points: i32) -> Self {
+        self.blitz_card_points = blitz_card_points;
+        self
+    }
+    pub fn with_completed_pile_bonus(mut self, completed_pile_bonus: i32) -> Self {
+        self.completed_pile_bonus = completed_pile_bonus;
+        self
+    }
+    pub fn with_blitz_bonus(mut self, blitz_bonus: i32) -> Self {
+        self.blitz_bonus = blitz_bonus;
+        self
+    }
+    pub fn with_win_condition(mut self, win_condition: proto::WinCondition) -> Self {
+        self.win_condition = win_condition;
+        self
+    }
+    pub fn with_round_limit(mut self, round_limit: u32) -> Self {
+        self.round_limit = round_limit;
+        self
+    }
+    pub fn with_round_wins_to_win(mut self, round_wins_to_win: u32) -> Self {
+        self.round_wins_to_win = round_wins_to_win;
+        self
+    }
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+    pub fn with_game_mode(mut self, game_mode: proto::GameMode) -> Self {
+        self.game_mode = game_mode;
+        self
+    }
+    pub fn with_cooperative_arena_goal(mut self, cooperative_arena_goal: u32) -> Self {
+        self.cooperative_arena_goal = cooperative_arena_goal;
+        self
+    }
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
     pub fn build(self) -> Result<GameState> {
         GameState::from_build(self)
     }
+    ///The prefs the builder describes, for use with [`GameState::new`] or [`tables::TableSet::new`].
+    pub fn build_prefs(self) -> proto::GamePrefs {
+        proto::GamePrefs {
+            post_pile_size: self.post_pile_size,
+            score_to_win: self.score_to_win,
+            blitz_deduction: self.blitz_deduction,
+            draw_rate: self.draw_rate,
+            auto_end_round: self.auto_end_round,
+            post_refill_rule: self.post_refill_rule as i32,
+            misplay_penalty: self.misplay_penalty,
+            misplay_lockout_ms: self.misplay_lockout.as_millis() as u32,
+            draw_rate_schedule: self.draw_rate_schedule,
+            wild_cards_per_player: self.wild_cards_per_player,
+            wild_card_bonus: self.wild_card_bonus,
+            arena_card_points: Some(self.arena_card_points),
+            blitz_card_points: Some(self.blitz_card_points),
+            completed_pile_bonus: self.completed_pile_bonus,
+            blitz_bonus: self.blitz_bonus,
+            win_condition: self.win_condition as i32,
+            round_limit: self.round_limit,
+            round_wins_to_win: self.round_wins_to_win,
+            time_limit_seconds: self.time_limit.as_secs() as u32,
+            game_mode: self.game_mode as i32,
+            cooperative_arena_goal: self.cooperative_arena_goal,
+        }
+    }
 }
 
 impl Default for GameStateBuilder {
@@ -120,6 +290,7 @@ impl Default for GameStateBuilder {
         Self::new()
     }
 }
+#[derive(Clone)]
 pub struct GameState {
     pub round: u32,
     pub scoreboard: Scoreboard,
@@ -132,73 +303,115 @@ pub struct GameState {
     pub score_to_win: u32,
     ///Amount of points to deduct if someone calls blitz on a player who can call blitz but has not.xs
     pub blitz_deduction: u32,
+    ///Whether the round ends as soon as any blitz pile is emptied, instead of waiting for the player to call blitz.
+    pub auto_end_round: bool,
+    ///How empty post piles get refilled from the blitz pile.
+    pub post_refill_rule: proto::PostRefillRule,
+    ///Points deducted for every rejected arena play.
+    pub misplay_penalty: u32,
+    ///How long a player is prevented from playing after a rejected arena play.
+    pub misplay_lockout: Duration,
+    ///Draw rates applied automatically by round and by time within a round.
+    pub draw_rate_schedule: Vec<proto::DrawRateStep>,
+    ///Number of wild cards added to each player's 40 card deck.
+    pub wild_cards_per_player: u32,
+    ///Points given for each wild card in the arena when the round is scored. Negative values are a penalty.
+    pub wild_card_bonus: i32,
+    ///Points for each of a player's cards in the arena. Defaults to 1
+    pub arena_card_points: i32,
+    ///Points for each card left in a player's blitz pile. Defaults to -2
+    pub blitz_card_points: i32,
+    ///Points for each arena pile a player completes by playing its 10.
+    pub completed_pile_bonus: i32,
+    ///Points for each player whose blitz pile is empty when the round is scored.
+    pub blitz_bonus: i32,
+    ///How the end of the game is decided.
+    pub win_condition: proto::WinCondition,
+    ///Number of rounds played under [`proto::WinCondition::FixedRounds`].
+    pub round_limit: u32,
+    ///Number of round wins needed under [`proto::WinCondition::RoundWins`].
+    pub round_wins_to_win: u32,
+    ///Length of the game under [`proto::WinCondition::TimeLimit`].
+    pub time_limit: Duration,
+    ///Whether players are eliminated between rounds.
+    pub game_mode: proto::GameMode,
+    ///The number of arena cards the team must reach in a cooperative game. When 0, every blitz pile must be emptied instead.
+    pub cooperative_arena_goal: u32,
     default_draw_rate: u32,
     is_game_over: bool,
+    ///Points deducted from each player during the current round, applied when the round is scored.
+    round_penalties: Vec<i32>,
+    ///When set, the player may not play until this instant.
+    locked_until: Vec<Option<Instant>>,
+    game_started: Instant,
+    round_started: Instant,
+    ///The index of the schedule step that was last applied, so that manual changes are only overridden when a new step begins.
+    applied_draw_rate_step: Option<usize>,
+    ///Plays accepted since the round started.
+    round_moves: u32,
+    ///Each player's plays this round that can still be undone, most recent last.
+    undo_stacks: Vec<Vec<UndoEntry>>,
+    ///Shuffles every deal.
+    rng: StdRng,
+}
+///A play that can be taken back in practice and solo games.
+#[derive(Clone)]
+struct UndoEntry {
+    ///The player's cards before the play.
+    player: Player,
+    ///The arena pile and card of an arena play.
+    arena_card: Option<(u32, u32)>,
 }
+///The error of an arena play whose card does not fit the pile it was played on. Only these plays count as misplays.
+#[derive(Debug)]
+pub struct Misplay(anyhow::Error);
+impl std::fmt::Display for Misplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+impl std::error::Error for Misplay {}
 impl GameState {
-    pub fn new(
-        player_count: u32,
-        proto::GamePrefs {
+    ///Creates a game from the given prefs. Fails with [`prefs::InvalidPrefs`] if they are not valid.
+    pub fn new(player_count: u32, prefs: proto::GamePrefs) -> Result<GameState> {
+        Self::with_rng(player_count, prefs, StdRng::from_entropy())
+    }
+    ///Like [`GameState::new`], but every deal of the game is the same for the same seed.
+    pub fn new_seeded(player_count: u32, prefs: proto::GamePrefs, seed: u64) -> Result<GameState> {
+        Self::with_rng(player_count, prefs, StdRng::seed_from_u64(seed))
+    }
+    fn with_rng(player_count: u32, prefs: proto::GamePrefs, rng: StdRng) -> Result<GameState> {
+        prefs::validate(player_count, &prefs)?;
+        let proto::GamePrefs {
             draw_rate,
             post_pile_size,
             score_to_win,
             blitz_deduction,
-        }: proto::GamePrefs,
-    ) -> Result<GameState> {
-        let cards = generate_all_card(player_count);
+            auto_end_round,
+            post_refill_rule,
+            misplay_penalty,
+            misplay_lockout_ms,
+            draw_rate_schedule,
+            wild_cards_per_player,
+            wild_card_bonus,
+            arena_card_points,
+            blitz_card_points,
+            completed_pile_bonus,
+            blitz_bonus,
+            win_condition,
+            round_limit,
+            round_wins_to_win,
+            time_limit_seconds,
+            game_mode,
+            cooperative_arena_goal,
+        } = prefs;
+        let cards = generate_all_card(player_count, wild_cards_per_player);
         let card_context = CardContext::new(cards);
-        //Once we have all the cards, we need to get player hands.
-        let mut players = Vec::new();
-        players.reserve(player_count as usize);
-
-        for i in 0..player_count {
-            //each player gets a 40 card hand. From the hand post_pile_size cards are removed and placed in the post pile,
-            // 10 cards are removed and placed in the blitz pile.
-            //the rest of the cards are placed in the player's hand.
-            let mut player_cards: Vec<u32> = card_context
-                .cards
-                .iter()
-                .enumerate()
-                .skip((i * 40) as usize)
-                .take(40)
-                .map(|(i, _c)| i as u32)
-                .collect();
-            shuffle(&mut player_cards);
-
-            let post_piles = player_cards
-                .iter()
-                .skip((40 - post_pile_size) as usize)
-                .take(post_pile_size as usize)
-                .copied()
-                .map(|i| {
-                    let card = card_context.cards[i as usize];
-                    Pile::from_vec(vec![i], card.color)
-                })
-                .collect::<Vec<_>>();
-            let blitz_pile = player_cards
-                .iter()
-                .skip((40 - post_pile_size - 10) as usize)
-                .take(10)
-                .copied()
-                .collect::<Vec<_>>();
-            let hand = player_cards
-                .iter()
-                .take((40 - post_pile_size - 10) as usize)
-                .copied()
-                .collect::<Vec<_>>();
-            players.push(Player {
-                player_id: i,
-                hand: PlayerHand::new(hand),
-                post_pile: PostPile { piles: post_piles },
-                blitz_pile: BlitzPile::new(blitz_pile),
-            });
-        }
-
         //the arena is initially empty.
         let arena = Arena::new();
-        Ok(GameState {
+        let mut game_state = GameState {
             card_context,
-            players,
+            players: Vec::with_capacity(player_count as usize),
             arena,
             draw_rate,
             round: 0,
@@ -206,40 +419,75 @@ impl GameState {
             post_pile_size,
             score_to_win,
             blitz_deduction,
+            auto_end_round,
+            post_refill_rule: proto::PostRefillRule::try_from(post_refill_rule)
+                .unwrap_or(proto::PostRefillRule::ManualRefill),
+            misplay_penalty,
+            misplay_lockout: Duration::from_millis(misplay_lockout_ms as u64),
+            draw_rate_schedule,
+            wild_cards_per_player,
+            wild_card_bonus,
+            arena_card_points: arena_card_points.unwrap_or(1),
+            blitz_card_points: blitz_card_points.unwrap_or(-2),
+            completed_pile_bonus,
+            blitz_bonus,
+            win_condition: proto::WinCondition::try_from(win_condition)
+                .unwrap_or(proto::WinCondition::ScoreToWin),
+            round_limit,
+            round_wins_to_win,
+            time_limit: Duration::from_secs(time_limit_seconds as u64),
+            game_mode: proto::GameMode::try_from(game_mode).unwrap_or(proto::GameMode::Classic),
+            cooperative_arena_goal,
             default_draw_rate: draw_rate,
             is_game_over: false,
-        })
+            round_penalties: vec![0; player_count as usize],
+            locked_until: vec![None; player_count as usize],
+            game_started: Instant::now(),
+            round_started: Instant::now(),
+            applied_draw_rate_step: None,
+            round_moves: 0,
+            undo_stacks: vec![vec![]; player_count as usize],
+            rng,
+        };
+        //Once we have all the cards, we need to get player hands.
+        for i in 0..player_count {
+            let player = game_state.create_player(i)?;
+            game_state.players.push(player);
+        }
+        Ok(game_state)
     }
 
     pub fn from_build(builder: GameStateBuilder) -> Result<GameState> {
-        GameState::new(
-            builder.player_count,
-            proto::GamePrefs {
-                post_pile_size: builder.post_pile_size,
-                score_to_win: builder.score_to_win,
-                blitz_deduction: builder.blitz_deduction,
-                draw_rate: builder.draw_rate,
-            },
-        )
+        let (player_count, seed) = (builder.player_count, builder.seed);
+        let prefs = builder.build_prefs();
+        match seed {
+            Some(seed) => GameState::new_seeded(player_count, prefs, seed),
+            None => GameState::new(player_count, prefs),
+        }
+    }
+    ///The number of cards in each player's deck: 40 plus any wild cards.
+    pub fn cards_per_player(&self) -> u32 {
+        40 + self.wild_cards_per_player
     }
-    pub fn create_player(&self, player_id: u32) -> Result<Player> {
-        //each player gets a 40 card hand. From the hand post_pile_size cards are removed and placed in the post pile,
+    pub fn create_player(&mut self, player_id: u32) -> Result<Player> {
+        //each player gets a 40 card hand (plus wild cards). From the hand post_pile_size cards are removed and placed in the post pile,
         // 10 cards are removed and placed in the blitz pile.
         //the rest of the cards are placed in the player's hand.
+        let deck_size = self.cards_per_player();
         let mut player_cards: Vec<u32> = self
             .card_context
             .cards
             .iter()
             .enumerate()
-            .skip((player_id * 40) as usize)
-            .take(40)
+            .skip((player_id * deck_size) as usize)
+            .take(deck_size as usize)
             .map(|(i, _c)| i as u32)
             .collect();
-        shuffle(&mut player_cards);
+        shuffle(&mut player_cards, &mut self.rng);
 
         let post_piles = player_cards
             .iter()
-            .skip((40 - self.post_pile_size) as usize)
+            .skip((deck_size - self.post_pile_size) as usize)
             .take(self.post_pile_size as usize)
             .copied()
             .map(|i| {
@@ -249,13 +497,13 @@ impl GameState {
             .collect::<Vec<_>>();
         let blitz_pile = player_cards
             .iter()
-            .skip((40 - self.post_pile_size - 10) as usize)
+            .skip((deck_size - self.post_pile_size - 10) as usize)
             .take(10)
             .copied()
             .collect::<Vec<_>>();
         let hand = player_cards
             .iter()
-            .take((40 - self.post_pile_size - 10) as usize)
+            .take((deck_size - self.post_pile_size - 10) as usize)
             .copied()
             .collect::<Vec<_>>();
         Ok(Player {
@@ -263,11 +511,15 @@ impl GameState {
             hand: PlayerHand::new(hand),
             post_pile: PostPile { piles: post_piles },
             blitz_pile: BlitzPile::new(blitz_pile),
+            eliminated_in_round: None,
         })
     }
 
     pub fn new_round(&mut self) -> Result<()> {
         self.round += 1;
+        self.round_started = Instant::now();
+        self.round_moves = 0;
+        self.undo_stacks.iter_mut().for_each(Vec::clear);
         //clear arena
         self.arena.piles.clear();
         //clear players
@@ -276,24 +528,46 @@ impl GameState {
             player.post_pile.clear();
             player.hand.clear();
         }
-        //create new players
-        self.players = (0..self.players.len())
-            .map(|i| self.create_player(i as u32).unwrap())
-            .collect();
+        self.locked_until.fill(None);
+        //deal again to the players who are still in the game. Eliminated players keep their seat, with no cards
+        for i in 0..self.players.len() {
+            if !self.players[i].is_eliminated() {
+                self.players[i] = self.create_player(i as u32)?;
+            }
+        }
         Ok(())
     }
 
     //Make a play. Emits an event describing whether a card was added/deleted to/from the arena, or whether a player's hand was modified.
     pub fn make_play(&mut self, play: Play) -> Result<proto::server_event::Event> {
         let player = play.player;
-        let event = match play.play {
+        if self.is_game_over {
+            return Err(anyhow!("Game is over"));
+        }
+        if self
+            .players
+            .get(player as usize)
+            .is_some_and(|p| p.is_eliminated())
+        {
+            return Err(anyhow!("Player has been eliminated"));
+        }
+        if matches!(play.play, Action::Arena(_) | Action::Player(_)) && self.is_locked_out(player) {
+            return Err(anyhow!("Player is locked out after a misplay"));
+        }
+        self.verify_post_refill_rule(&play)?;
+        let undo_snapshot = (self.allows_undo()
+            && matches!(play.play, Action::Arena(_) | Action::Player(_)))
+        .then(|| self.players.get(player as usize).cloned())
+        .flatten();
+        let mut event = match play.play {
             Action::Arena(p) => match p {
                 ArenaAction::FromAvailableHand(pile) => {
                     let play_vtoken = self.players[player as usize]
                         .hand
                         .verify_play_from_available()?;
                     self.arena
-                        .verify_add_card(pile, play_vtoken, &self.card_context)?;
+                        .verify_add_card(pile, play_vtoken, &self.card_context)
+                        .map_err(Misplay)?;
                     let play_card = self.players[player as usize].hand.play_from_available()?;
                     self.arena.add_card(pile, play_card, &self.card_context)?;
                     //emit event
@@ -315,9 +589,10 @@ impl GameState {
                     })
                 }
                 ArenaAction::FromBlitz(pile) => {
-                    self.players[player as usize].blitz_pile.verify_play()?;
+                    let play_vtoken = self.players[player as usize].blitz_pile.verify_play()?;
                     self.arena
-                        .verify_add_card(pile, player, &self.card_context)?;
+                        .verify_add_card(pile, play_vtoken, &self.card_context)
+                        .map_err(Misplay)?;
                     let card = self.players[player as usize].blitz_pile.play()?;
                     self.arena.add_card(pile, card, &self.card_context)?;
                     //emit event
@@ -351,7 +626,8 @@ impl GameState {
                         .verify_play(post_pile)?;
                     tracing::info!("Verified play from post pile");
                     self.arena
-                        .verify_add_card(arena_pile, play_vtoken, &self.card_context)?;
+                        .verify_add_card(arena_pile, play_vtoken, &self.card_context)
+                        .map_err(Misplay)?;
                     tracing::info!("Verified add to arena");
                     let card = self.players[player as usize].post_pile.play(post_pile)?;
                     self.arena.add_card(arena_pile, card, &self.card_context)?;
@@ -493,6 +769,44 @@ impl GameState {
                             }],
                         })
                     }
+                    PlayerAction::PostToPost { from, to } => {
+                        if from == to {
+                            return Err(anyhow!("Cannot move a post card onto its own pile"));
+                        }
+                        let play_vtoken =
+                            self.players[player as usize].post_pile.verify_play(from)?;
+                        self.players[player as usize].post_pile.verify_add_card(
+                            to,
+                            play_vtoken,
+                            &self.card_context,
+                        )?;
+                        let card = self.players[player as usize].post_pile.play(from)?;
+                        self.players[player as usize].post_pile.add_card(
+                            to,
+                            card,
+                            &self.card_context,
+                        )?;
+
+                        proto::server_event::Event::GameStateChange(GameStateChange {
+                            arena_state_changes: vec![],
+                            player_state_changes: vec![PlayerStateChange {
+                                player_id: player,
+                                new_hand_pile: None,
+                                new_available_hand: None,
+                                new_blitz_pile: None,
+                                new_post_pile: Some(proto::PostPile {
+                                    piles: self.players[player as usize]
+                                        .post_pile
+                                        .piles
+                                        .iter()
+                                        .map(|p| proto::Pile {
+                                            cards: p.cards.clone(),
+                                        })
+                                        .collect(),
+                                }),
+                            }],
+                        })
+                    }
                     PlayerAction::ResetHand => {
                         self.players[player as usize].hand.reset_hand();
 
@@ -511,15 +825,36 @@ impl GameState {
                     }
                 }
             }
+            //cooperative and solo games are a single round, and calling blitz ends it with the team's or the run's result
+            Action::CallBlitz(_p)
+                if matches!(
+                    self.game_mode,
+                    proto::GameMode::Cooperative | proto::GameMode::Solo
+                ) =>
+            {
+                //one early call would otherwise lose the game for the whole team
+                if self.game_mode == proto::GameMode::Cooperative
+                    && !self.cooperative_goal_reached()
+                {
+                    return Err(anyhow!("The team has not reached its goal yet"));
+                }
+                proto::server_event::Event::RoundOver(self.end_round()?)
+            }
             Action::CallBlitz(_p) => {
                 //when blitz is called,we count up all the cards in the arena, and give players points depending upon how many cards they played.
                 if self.players[player as usize].can_call_blitz() {
                     //everything is normal, new round
                     self.score_round();
-                    self.new_round()?;
-                    proto::server_event::Event::ServerGameStateAction(
-                        ServerGameStateAction::ServerNewRound as i32,
-                    )
+                    if self.is_game_over {
+                        proto::server_event::Event::ServerGameStateAction(
+                            ServerGameStateAction::ServerGameOver as i32,
+                        )
+                    } else {
+                        self.new_round()?;
+                        proto::server_event::Event::ServerGameStateAction(
+                            ServerGameStateAction::ServerNewRound as i32,
+                        )
+                    }
                 } else {
                     let blitzed_players: Vec<u32> = self
                         .players
@@ -532,8 +867,7 @@ impl GameState {
                     for p in blitzed_players {
                         //these players get blitz_deduction points deducted from their score.
                         //deduct points from the player
-                        self.scoreboard
-                            .add_score(self.round, p, -(self.blitz_deduction as i32));
+                        self.round_penalties[p as usize] -= self.blitz_deduction as i32;
                     }
                     self.score_round();
                     proto::server_event::Event::ServerGameStateAction(
@@ -542,80 +876,695 @@ impl GameState {
                 }
             }
         };
+        if self.post_refill_rule == proto::PostRefillRule::AutomaticRefill
+            && matches!(play.play, Action::Arena(_) | Action::Player(_))
+            && self.refill_post_piles(player)?
+        {
+            if let proto::server_event::Event::GameStateChange(change) = &mut event {
+                let player_state = &self.players[player as usize];
+                let new_blitz_pile = Some(proto::Pile {
+                    cards: player_state.blitz_pile.cards.clone(),
+                });
+                let new_post_pile = Some(player_state.post_pile.to_proto());
+                match change
+                    .player_state_changes
+                    .iter_mut()
+                    .find(|c| c.player_id == player)
+                {
+                    Some(c) => {
+                        c.new_blitz_pile = new_blitz_pile;
+                        c.new_post_pile = new_post_pile;
+                    }
+                    None => change.player_state_changes.push(PlayerStateChange {
+                        player_id: player,
+                        new_hand_pile: None,
+                        new_available_hand: None,
+                        new_blitz_pile,
+                        new_post_pile,
+                    }),
+                }
+            }
+        }
+        if matches!(play.play, Action::Arena(_) | Action::Player(_)) {
+            self.round_moves += 1;
+        }
+        if let Some(snapshot) = undo_snapshot {
+            //the played card is on top of whichever pile it went to
+            let arena_card = match &event {
+                proto::server_event::Event::GameStateChange(change) => {
+                    change.arena_state_changes.first().and_then(|c| {
+                        let pile = self
+                            .arena
+                            .piles
+                            .iter()
+                            .position(|p| p.cards.last() == Some(&c.card))?;
+                        Some((pile as u32, c.card))
+                    })
+                }
+                _ => None,
+            };
+            self.undo_stacks[player as usize].push(UndoEntry {
+                player: snapshot,
+                arena_card,
+            });
+        }
         Ok(event)
     }
+    ///Whether plays may be undone. Only practice and solo games allow it.
+    pub fn allows_undo(&self) -> bool {
+        matches!(
+            self.game_mode,
+            proto::GameMode::Practice | proto::GameMode::Solo
+        )
+    }
+    ///Whether players may ask for hints. Only practice and solo games allow it.
+    pub fn allows_hints(&self) -> bool {
+        self.allows_undo()
+    }
+    ///Takes back the player's most recent play this round. An arena play can only be taken back while its card is still on top of its pile,
+    /// and a card that started a pile only while that is the newest pile.
+    pub fn undo(&mut self, player: u32) -> Result<proto::server_event::Event> {
+        if !self.allows_undo() {
+            return Err(anyhow!("Undo is only allowed in practice and solo games"));
+        }
+        let stack = self
+            .undo_stacks
+            .get_mut(player as usize)
+            .ok_or_else(|| anyhow!("Player index out of bounds"))?;
+        let entry = stack.last().ok_or_else(|| anyhow!("Nothing to undo"))?;
+        let mut arena_state_changes = vec![];
+        if let Some((pile_index, card)) = entry.arena_card {
+            let piles = &mut self.arena.piles;
+            let pile = piles
+                .get(pile_index as usize)
+                .filter(|p| p.cards.last() == Some(&card))
+                .ok_or_else(|| anyhow!("The card has been covered in the arena"))?;
+            if pile.cards.len() > 1 {
+                piles[pile_index as usize].cards.pop();
+            } else if pile_index as usize == piles.len() - 1 {
+                piles.pop();
+            } else {
+                return Err(anyhow!("Newer piles have been started in the arena"));
+            }
+            arena_state_changes.push(ArenaStateChange {
+                action: proto::StateChangeAction::Remove as i32,
+                card,
+                pile_index,
+            });
+        }
+        let entry = stack.pop().expect("the stack was checked to not be empty");
+        //every play that can be undone was counted as a move
+        self.round_moves -= 1;
+        let player_state = &mut self.players[player as usize];
+        *player_state = entry.player;
+        Ok(proto::server_event::Event::GameStateChange(
+            GameStateChange {
+                arena_state_changes,
+                player_state_changes: vec![PlayerStateChange {
+                    player_id: player,
+                    new_hand_pile: Some(proto::Pile {
+                        cards: player_state.hand.in_hand.clone(),
+                    }),
+                    new_available_hand: Some(proto::Pile {
+                        cards: player_state.hand.available_to_play.clone(),
+                    }),
+                    new_blitz_pile: Some(proto::Pile {
+                        cards: player_state.blitz_pile.cards.clone(),
+                    }),
+                    new_post_pile: Some(player_state.post_pile.to_proto()),
+                }],
+            },
+        ))
+    }
 
+    pub fn is_locked_out(&self, player: u32) -> bool {
+        self.locked_until
+            .get(player as usize)
+            .copied()
+            .flatten()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    ///Applies the misplay penalty and lock-out to the player whose play was rejected with `error`, if it was a [`Misplay`].
+    /// Plays rejected for any other reason, such as during a lock-out, are not penalized. Returns whether a penalty was applied.
+    pub fn penalize_misplay(&mut self, play: &Play, error: &anyhow::Error) -> bool {
+        let player = play.player as usize;
+        if !error.is::<Misplay>() || player >= self.players.len() {
+            return false;
+        }
+        self.round_penalties[player] -= self.misplay_penalty as i32;
+        if !self.misplay_lockout.is_zero() {
+            self.locked_until[player] = Some(Instant::now() + self.misplay_lockout);
+        }
+        self.misplay_penalty > 0 || !self.misplay_lockout.is_zero()
+    }
+
+    ///Under [`proto::PostRefillRule::ForcedRefill`], a player with an empty post pile and cards left in their blitz pile may only move a blitz card onto an empty post pile.
+    fn verify_post_refill_rule(&self, play: &Play) -> Result<()> {
+        if self.post_refill_rule != proto::PostRefillRule::ForcedRefill {
+            return Ok(());
+        }
+        let player = self
+            .players
+            .get(play.player as usize)
+            .ok_or_else(|| anyhow!("Player index out of bounds"))?;
+        let is_empty = |p: u32| {
+            player
+                .post_pile
+                .piles
+                .get(p as usize)
+                .is_some_and(|p| p.cards.is_empty())
+        };
+        let needs_refill = !player.blitz_pile.cards.is_empty()
+            && player.post_pile.piles.iter().any(|p| p.cards.is_empty());
+        match play.play {
+            Action::Player(PlayerAction::BlitzToPost(p)) if is_empty(p) => Ok(()),
+            Action::Arena(_) | Action::Player(_) if needs_refill => Err(anyhow!(
+                "Empty post piles must be refilled from the blitz pile first"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    ///Moves the top card of the player's blitz pile onto each of their empty post piles, for as long as the blitz pile lasts.
+    /// Returns whether any card was moved.
+    pub fn refill_post_piles(&mut self, player: u32) -> Result<bool> {
+        let player = self
+            .players
+            .get_mut(player as usize)
+            .ok_or_else(|| anyhow!("Player index out of bounds"))?;
+        let mut refilled = false;
+        for pile in player.post_pile.piles.iter_mut() {
+            if !pile.cards.is_empty() {
+                continue;
+            }
+            let Ok(card) = player.blitz_pile.play() else {
+                break;
+            };
+            pile.add_post_card(card, &self.card_context)?;
+            refilled = true;
+        }
+        Ok(refilled)
+    }
+
+    ///Each player's points for the round so far from their cards in the arena and in their blitz pile, before any bonuses or penalties.
+    pub fn round_points(&self) -> Vec<i32> {
+        let mut points = vec![0; self.players.len()];
+        for card in self.arena.piles.iter().flat_map(|p| p.cards.iter()) {
+            points[self.card_context.cards[*card as usize].player_id as usize] +=
+                self.arena_card_points;
+        }
+        for (points, p) in points.iter_mut().zip(self.players.iter()) {
+            *points += p.blitz_pile.cards.len() as i32 * self.blitz_card_points;
+        }
+        points
+    }
     ///Counts up all the cards in the arena, and gives players points depending upon how many cards they played. Called at the end of a round (when blitz is called).
-    /// We also count up how many cards are left in the blitz pile and subtract 2* that number from the player's score.
+    /// We also count up how many cards are left in the blitz pile and score them (-2 each by default), and add any bonuses and penalties from the round.
     pub fn score_round(&mut self) {
-        let mut player_arena_scores = vec![0; self.players.len()];
+        self.score_round_at(Instant::now())
+    }
+    ///Like [`GameState::score_round`], deciding time-based win conditions as of `now`.
+    fn score_round_at(&mut self, now: Instant) {
+        let mut round_scores = vec![RoundScore::default(); self.players.len()];
         for pile in self.arena.piles.iter() {
             for card in pile.cards.iter() {
                 let card = self.card_context.cards[*card as usize];
-                player_arena_scores[card.player_id as usize] += 1;
+                let score = &mut round_scores[card.player_id as usize];
+                score.arena_cards += 1;
+                score.arena_points += self.arena_card_points;
+                if card.is_wild() {
+                    score.wild_card_points += self.wild_card_bonus;
+                }
+            }
+            //whoever played the 10 completed the pile
+            if let (10, Some(top)) = (pile.cards.len(), pile.cards.last()) {
+                let card = self.card_context.cards[*top as usize];
+                round_scores[card.player_id as usize].completed_pile_points +=
+                    self.completed_pile_bonus;
             }
         }
-        let blitz_scores = self
+        for ((player, score), penalty) in self
             .players
             .iter()
-            .map(|p| p.blitz_pile.cards.len() as i32 * -2)
-            .collect::<Vec<_>>();
-        player_arena_scores = player_arena_scores
+            .zip(round_scores.iter_mut())
+            .zip(self.round_penalties.iter())
+        {
+            score.blitz_cards_left = player.blitz_pile.cards.len() as u32;
+            score.blitz_points = score.blitz_cards_left as i32 * self.blitz_card_points;
+            if player.can_call_blitz() {
+                score.blitz_bonus = self.blitz_bonus;
+            }
+            score.penalty_points = *penalty;
+        }
+        self.round_penalties.fill(0);
+        self.scoreboard.add_round(self.round, round_scores);
+        if self.game_mode == proto::GameMode::Elimination {
+            self.eliminate_last_player();
+        }
+        if self.reached_win_condition(now) {
+            self.is_game_over = true;
+        }
+    }
+    ///Eliminates the remaining player with the lowest total. Ties are lost by whoever comes last in [`Scoreboard::tie_break_order`].
+    /// Returns the eliminated player, if any was.
+    fn eliminate_last_player(&mut self) -> Option<u32> {
+        if self.players_remaining() <= 1 {
+            return None;
+        }
+        let totals = self.scoreboard.get_totals();
+        let eliminated = self
+            .scoreboard
+            .tie_break_order()
+            .into_iter()
+            .rev()
+            .filter(|p| !self.players[*p as usize].is_eliminated())
+            .min_by_key(|p| totals[*p as usize])?;
+        let player = &mut self.players[eliminated as usize];
+        player.eliminated_in_round = Some(self.round);
+        player.hand.clear();
+        player.post_pile.clear();
+        player.blitz_pile.clear();
+        Some(eliminated)
+    }
+    ///The number of players who have not been eliminated.
+    pub fn players_remaining(&self) -> usize {
+        self.players.iter().filter(|p| !p.is_eliminated()).count()
+    }
+
+    ///Whether the game should end once the current round has been scored.
+    fn reached_win_condition(&self, now: Instant) -> bool {
+        match self.game_mode {
+            proto::GameMode::Classic | proto::GameMode::Practice => {}
+            proto::GameMode::Elimination => return self.players_remaining() <= 1,
+            //a cooperative game is a single attempt
+            proto::GameMode::Cooperative | proto::GameMode::Solo => return true,
+        }
+        match self.win_condition {
+            //if any player has a score equal to or greater than the win score, the game is over.
+            proto::WinCondition::ScoreToWin => self
+                .scoreboard
+                .get_totals()
+                .iter()
+                .any(|s| *s >= self.score_to_win as i32),
+            proto::WinCondition::FixedRounds => self.round + 1 >= self.round_limit,
+            proto::WinCondition::RoundWins => self
+                .scoreboard
+                .round_wins()
+                .iter()
+                .any(|w| *w >= self.round_wins_to_win),
+            proto::WinCondition::TimeLimit => {
+                now.saturating_duration_since(self.game_started) >= self.time_limit
+            }
+        }
+    }
+
+    ///Player ids ordered from first to last place. Under [`proto::WinCondition::RoundWins`] players are ranked by round wins first, otherwise by total score.
+    /// Ties are broken by [`Scoreboard::tie_break_order`]. In elimination mode, players still in the game come first, followed by the eliminated players from last to first out.
+    pub fn standings(&self) -> Vec<u32> {
+        let primary = match self.win_condition {
+            proto::WinCondition::RoundWins => self
+                .scoreboard
+                .round_wins()
+                .into_iter()
+                .map(|w| w as i32)
+                .collect(),
+            _ => self.scoreboard.get_totals(),
+        };
+        //the sort is stable, so tied players keep their tie-break order
+        let mut standings = self.scoreboard.tie_break_order();
+        standings.sort_by_key(|p| std::cmp::Reverse(primary[*p as usize]));
+        standings.sort_by_key(|p| {
+            std::cmp::Reverse(
+                self.players[*p as usize]
+                    .eliminated_in_round
+                    .unwrap_or(u32::MAX),
+            )
+        });
+        standings
+    }
+    ///The player who won the game, once it is over. Cooperative games are won or lost by the whole team instead.
+    pub fn winner(&self) -> Option<u32> {
+        if !self.is_game_over || self.game_mode == proto::GameMode::Cooperative {
+            return None;
+        }
+        self.standings().first().copied()
+    }
+
+    ///Whether the round should be ended by the server without anyone calling blitz. True when `auto_end_round` is set and some player has emptied their blitz pile,
+    /// when the goal of a cooperative game has been reached, or when the blitz pile of a solo game is empty. Never true once the game is over.
+    pub fn should_end_round(&self) -> bool {
+        if self.is_game_over {
+            return false;
+        }
+        match self.game_mode {
+            proto::GameMode::Cooperative => return self.cooperative_goal_reached(),
+            proto::GameMode::Solo => return self.players.iter().all(|p| p.can_call_blitz()),
+            _ => {}
+        }
+        self.auto_end_round && self.players.iter().any(|p| p.can_call_blitz())
+    }
+    ///Whether the team has reached the goal of a cooperative game.
+    pub fn cooperative_goal_reached(&self) -> bool {
+        if self.cooperative_arena_goal > 0 {
+            let arena_cards: usize = self.arena.piles.iter().map(|p| p.cards.len()).sum();
+            arena_cards >= self.cooperative_arena_goal as usize
+        } else {
+            self.players.iter().all(|p| p.can_call_blitz())
+        }
+    }
+    ///Scores the current round and, unless that ends the game, deals the next one. Returns the event describing the results.
+    /// Fails once the game is over.
+    pub fn end_round(&mut self) -> Result<proto::RoundOverEvent> {
+        self.end_round_at(Instant::now())
+    }
+    fn end_round_at(&mut self, now: Instant) -> Result<proto::RoundOverEvent> {
+        if self.is_game_over {
+            return Err(anyhow!("Game is over"));
+        }
+        let round = self.round;
+        //checked before scoring, while the arena still holds the round's cards
+        let team_won = self.cooperative_goal_reached();
+        let solo_result = self.solo_result();
+        self.score_round_at(now);
+        let totals = self.scoreboard.get_totals();
+        let scores = self
+            .scoreboard
+            .breakdowns
             .iter()
-            .zip(blitz_scores.iter())
-            .map(|(a, b)| a + b)
+            .zip(totals)
+            .enumerate()
+            .map(|(i, (s, total))| {
+                let s = s.last().copied().unwrap_or_default();
+                proto::PlayerRoundScore {
+                    is_eliminated: self.players[i].is_eliminated(),
+                    ..s.to_proto(i as u32, total)
+                }
+            })
             .collect();
-        self.scoreboard.add_round(self.round, player_arena_scores);
-        //if any player has a score equal to or greater than the win score, the game is over.
-        let player_total_scores = self.scoreboard.get_totals();
-        if player_total_scores
+        let eliminated = self
+            .players
             .iter()
-            .any(|s| *s >= self.score_to_win as i32)
-        {
-            self.is_game_over = true;
+            .find(|p| p.eliminated_in_round == Some(round))
+            .map(|p| p.player_id);
+        let cooperative = self.game_mode == proto::GameMode::Cooperative;
+        let player_cards = if self.is_game_over {
+            vec![]
+        } else {
+            self.new_round()?;
+            self.player_cards()
+        };
+        Ok(proto::RoundOverEvent {
+            round,
+            scores,
+            is_game_over: self.is_game_over,
+            player_cards,
+            standings: self.standings(),
+            winner: self.winner(),
+            eliminated,
+            team_won: cooperative.then_some(team_won),
+            team_score: cooperative.then(|| self.scoreboard.get_totals().iter().sum()),
+            solo_result,
+        })
+    }
+    pub fn is_game_over(&self) -> bool {
+        self.is_game_over
+    }
+    ///How the round in progress of a solo game has gone so far.
+    pub fn solo_result(&self) -> Option<proto::SoloResult> {
+        if self.game_mode != proto::GameMode::Solo {
+            return None;
+        }
+        Some(proto::SoloResult {
+            completed: self.players.iter().all(|p| p.can_call_blitz()),
+            completion_ms: self.round_started.elapsed().as_millis() as u32,
+            moves: self.round_moves,
+        })
+    }
+    ///Every card in the game. Cards everywhere else are indices into this deck.
+    pub fn global_deck(&self) -> proto::GlobalDeck {
+        proto::GlobalDeck {
+            cards: self
+                .card_context
+                .cards
+                .iter()
+                .map(|e| proto::Card {
+                    player_id: e.player_id,
+                    number: e.number,
+                    color: e.color as i32,
+                    gender: e.gender as i32,
+                    kind: e.kind as i32,
+                })
+                .collect(),
         }
     }
+    ///Everything a client needs to show the game, or to keep playing it with the same rules.
+    pub fn snapshot(&self) -> proto::GameSnapshot {
+        proto::GameSnapshot {
+            round: self.round,
+            draw_rate: self.draw_rate,
+            players: self.player_cards(),
+            arena: self
+                .arena
+                .piles
+                .iter()
+                .map(|p| proto::Pile {
+                    cards: p.cards.clone(),
+                })
+                .collect(),
+            total_scores: self.scoreboard.get_totals(),
+            is_game_over: self.is_game_over,
+        }
+    }
+    ///The cards currently held by each player, in the form sent to clients.
+    pub fn player_cards(&self) -> Vec<proto::PlayerCards> {
+        self.players
+            .iter()
+            .map(|p| proto::PlayerCards {
+                hand: Some(proto::PlayerHand {
+                    in_hand: p.hand.in_hand.clone(),
+                    available_to_play: p.hand.available_to_play.clone(),
+                }),
+                post: Some(proto::PostPile {
+                    piles: p
+                        .post_pile
+                        .piles
+                        .iter()
+                        .map(|e| proto::Pile {
+                            cards: e.cards.clone(),
+                        })
+                        .collect(),
+                }),
+                blitz_pile: p.blitz_pile.cards.clone(),
+            })
+            .collect()
+    }
 
     pub fn change_draw_rate(&mut self, new_rate: u32) {
         self.draw_rate = new_rate;
     }
+    ///Resets the draw rate to the one the schedule currently calls for, or to the starting draw rate if no step applies.
     pub fn reset_draw_rate(&mut self) {
-        self.draw_rate = self.default_draw_rate;
+        self.draw_rate = self
+            .scheduled_draw_rate_step(Instant::now())
+            .map(|i| self.draw_rate_schedule[i].draw_rate)
+            .unwrap_or(self.default_draw_rate);
+    }
+    ///The index of the most specific step of the draw rate schedule that applies at `now`.
+    fn scheduled_draw_rate_step(&self, now: Instant) -> Option<usize> {
+        let elapsed = now.saturating_duration_since(self.round_started);
+        self.draw_rate_schedule
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| {
+                step.from_round <= self.round
+                    && elapsed >= Duration::from_secs(step.after_seconds.unwrap_or(0) as u64)
+            })
+            .max_by_key(|(i, step)| (step.from_round, step.after_seconds.unwrap_or(0), *i))
+            .map(|(i, _)| i)
+    }
+    ///Applies the draw rate schedule. Returns the new draw rate when a different step has started applying since the last call,
+    /// or when no step applies any more and the starting draw rate is back.
+    pub fn apply_draw_rate_schedule(&mut self, now: Instant) -> Option<u32> {
+        let step = self.scheduled_draw_rate_step(now);
+        if step == self.applied_draw_rate_step {
+            return None;
+        }
+        self.applied_draw_rate_step = step;
+        self.draw_rate = match step {
+            Some(step) => self.draw_rate_schedule[step].draw_rate,
+            None => self.default_draw_rate,
+        };
+        Some(self.draw_rate)
+    }
+    ///Whether the game ends once `time_limit` has passed.
+    fn is_timed(&self) -> bool {
+        match self.game_mode {
+            proto::GameMode::Classic | proto::GameMode::Practice => {
+                self.win_condition == proto::WinCondition::TimeLimit
+            }
+            proto::GameMode::Elimination | proto::GameMode::Solo => false,
+            proto::GameMode::Cooperative => true,
+        }
+    }
+    ///Advances the parts of the game that depend on time rather than on plays. Called periodically by the server.
+    /// Returns the events that should be sent to every player.
+    pub fn tick(&mut self, now: Instant) -> Result<Vec<proto::server_event::Event>> {
+        let mut events = vec![];
+        if self.is_game_over {
+            return Ok(events);
+        }
+        if self.is_timed() && now.saturating_duration_since(self.game_started) >= self.time_limit {
+            //time is up, so the round in progress is the last one
+            events.push(proto::server_event::Event::RoundOver(
+                self.end_round_at(now)?,
+            ));
+            return Ok(events);
+        }
+        if let Some(new_rate) = self.apply_draw_rate_schedule(now) {
+            events.push(proto::server_event::Event::ChangeDrawRate(
+                proto::ChangeDrawRateEvent { new_rate },
+            ));
+        }
+        Ok(events)
     }
 }
 
+#[derive(Clone)]
 pub struct Scoreboard {
     //holds per round scores for each player.
     pub scores: Vec<Vec<i32>>,
+    //holds the components of each per round score, in the same layout as `scores`.
+    pub breakdowns: Vec<Vec<RoundScore>>,
 }
 impl Scoreboard {
     pub fn new(player_count: u32) -> Scoreboard {
-        let mut scores = Vec::new();
-        scores.reserve(player_count as usize);
+        let mut scores = Vec::with_capacity(player_count as usize);
+        let mut breakdowns = Vec::with_capacity(player_count as usize);
         for _ in 0..player_count {
             scores.push(vec![]);
+            breakdowns.push(vec![]);
         }
-        Scoreboard { scores }
+        Scoreboard { scores, breakdowns }
     }
     pub fn get_totals(&self) -> Vec<i32> {
-        let mut totals = Vec::new();
-        totals.reserve(self.scores.len());
+        let mut totals = Vec::with_capacity(self.scores.len());
         for score in &self.scores {
             totals.push(score.iter().sum());
         }
         totals
     }
-    pub fn add_round(&mut self, _round: u32, scores: Vec<i32>) {
+    pub fn add_round(&mut self, _round: u32, scores: Vec<RoundScore>) {
         for (i, score) in scores.into_iter().enumerate() {
-            self.scores[i].push(score);
+            self.scores[i].push(score.total());
+            self.breakdowns[i].push(score);
+        }
+    }
+    ///The sum of every component of each player's round scores.
+    pub fn total_breakdowns(&self) -> Vec<RoundScore> {
+        self.breakdowns
+            .iter()
+            .map(|rounds| {
+                rounds
+                    .iter()
+                    .fold(RoundScore::default(), |acc, r| RoundScore {
+                        arena_cards: acc.arena_cards + r.arena_cards,
+                        blitz_cards_left: acc.blitz_cards_left + r.blitz_cards_left,
+                        arena_points: acc.arena_points + r.arena_points,
+                        blitz_points: acc.blitz_points + r.blitz_points,
+                        wild_card_points: acc.wild_card_points + r.wild_card_points,
+                        completed_pile_points: acc.completed_pile_points + r.completed_pile_points,
+                        blitz_bonus: acc.blitz_bonus + r.blitz_bonus,
+                        penalty_points: acc.penalty_points + r.penalty_points,
+                    })
+            })
+            .collect()
+    }
+    ///Player ids ordered by the tie-break rules: fewest points lost to blitz cards, then most arena cards, then lowest player id.
+    pub fn tie_break_order(&self) -> Vec<u32> {
+        let totals = self.total_breakdowns();
+        let mut order: Vec<u32> = (0..totals.len() as u32).collect();
+        order.sort_by_key(|p| {
+            let t = &totals[*p as usize];
+            (
+                std::cmp::Reverse(t.blitz_points),
+                std::cmp::Reverse(t.arena_cards),
+                *p,
+            )
+        });
+        order
+    }
+    ///How many rounds each player won. A round is won by the best score in that round, with ties broken the same way as [`Scoreboard::tie_break_order`] but for that round alone.
+    pub fn round_wins(&self) -> Vec<u32> {
+        let mut wins = vec![0; self.scores.len()];
+        let rounds = self.scores.iter().map(|s| s.len()).min().unwrap_or(0);
+        for round in 0..rounds {
+            let winner = (0..self.scores.len()).min_by_key(|p| {
+                let r = &self.breakdowns[*p][round];
+                (
+                    std::cmp::Reverse(self.scores[*p][round]),
+                    std::cmp::Reverse(r.blitz_points),
+                    std::cmp::Reverse(r.arena_cards),
+                    *p,
+                )
+            });
+            if let Some(winner) = winner {
+                wins[winner] += 1;
+            }
         }
+        wins
     }
+    ///Adds points to a round that has already been scored. They are counted as penalty points in the breakdown.
     pub fn add_score(&mut self, round: u32, player: u32, score: i32) {
-        self.scores[round as usize][player as usize] += score;
+        self.scores[player as usize][round as usize] += score;
+        self.breakdowns[player as usize][round as usize].penalty_points += score;
+    }
+}
+
+///How a player's score for one round is made up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RoundScore {
+    ///How many of the player's cards are in the arena.
+    pub arena_cards: u32,
+    ///How many cards are left in the player's blitz pile.
+    pub blitz_cards_left: u32,
+    pub arena_points: i32,
+    pub blitz_points: i32,
+    pub wild_card_points: i32,
+    pub completed_pile_points: i32,
+    pub blitz_bonus: i32,
+    ///Misplay penalties and blitz deductions.
+    pub penalty_points: i32,
+}
+impl RoundScore {
+    pub fn total(&self) -> i32 {
+        self.arena_points
+            + self.blitz_points
+            + self.wild_card_points
+            + self.completed_pile_points
+            + self.blitz_bonus
+            + self.penalty_points
+    }
+    pub fn to_proto(&self, player_id: u32, total_score: i32) -> proto::PlayerRoundScore {
+        proto::PlayerRoundScore {
+            player_id,
+            round_score: self.total(),
+            total_score,
+            arena_cards: self.arena_cards,
+            blitz_cards_left: self.blitz_cards_left,
+            arena_points: self.arena_points,
+            blitz_points: self.blitz_points,
+            wild_card_points: self.wild_card_points,
+            completed_pile_points: self.completed_pile_points,
+            blitz_bonus: self.blitz_bonus,
+            penalty_points: self.penalty_points,
+            is_eliminated: false,
+        }
     }
 }
 
 ///a pile is a stack of less <=10 cards.
+#[derive(Clone)]
 pub struct Pile {
     pub cards: Vec<u32>,
     pub color: Color,
@@ -625,34 +1574,20 @@ impl Pile {
         Pile { cards, color }
     }
     pub fn add_arena_card(&mut self, card_index: u32, context: &CardContext) -> Result<()> {
-        if self.cards.len() == 10 {
-            return Err(anyhow!("Pile is full"));
-        }
-
-        let card = context.get_card(card_index as usize)?;
-        if card.color != self.color {
-            return Err(anyhow!(
-                "Card color {card:?} does not match pile color {pile:?}",
-                pile = self.color
-            ));
-        }
-
-        if card.number != (self.cards.len() + 1) as u32 {
-            return Err(anyhow!(
-                "Card number {} does not match pile counter {}",
-                card.number,
-                self.cards.len()
-            ));
-        }
+        self.verify_add_arena_card(card_index, context)?;
         self.cards.push(card_index);
         Ok(())
     }
+    ///Arena piles are built up in the pile's color from 1 to 10. A wild card can stand in for the next card on any pile.
     pub fn verify_add_arena_card(&self, card_index: u32, context: &CardContext) -> Result<()> {
         if self.cards.len() == 10 {
             return Err(anyhow!("Pile is full"));
         }
 
         let card = context.get_card(card_index as usize)?;
+        if card.is_wild() {
+            return Ok(());
+        }
 
         if card.color != self.color {
             return Err(anyhow!(
@@ -677,34 +1612,14 @@ impl Pile {
     }
     ///When stacking on the post pile, the card must be the same color, the natural anteceding number,and the gender must be the opposite of the previous card.
     pub fn add_post_card(&mut self, card_index: u32, context: &CardContext) -> Result<()> {
-        if self.cards.len() == 10 {
-            return Err(anyhow!("Pile is full"));
-        }
         tracing::info!(current_size=self.cards.len(),current_color=?self.color, "Adding card to post pile");
+        self.verify_add_post_card(card_index, context)?;
         let card = context.get_card(card_index as usize)?;
-        if self.cards.is_empty() {
+        //the first normal card on a pile decides its color
+        if !card.is_wild() && self.post_pile_top(context)?.is_none() {
             self.color = card.color;
-            self.cards.push(card_index);
-            return Ok(());
-        }
-        if card.color != self.color {
-            return Err(anyhow!(
-                "Card color {card:?} does not match pile color {color:?}",
-                color = self.color
-            ));
-        }
-        let prev_card = context.get_card(self.cards[self.cards.len() - 1] as usize)?;
-        //genders must not be the same
-        if card.gender == prev_card.gender {
-            return Err(anyhow!("Genders must alternate"));
-        }
-        if card.number != (prev_card.number - 1) {
-            return Err(anyhow!(
-                "Card number {card:?} does not match pile counter {prev_card:?}"
-            ));
         }
         self.cards.push(card_index);
-
         Ok(())
     }
     pub fn verify_add_post_card(&self, card_index: u32, context: &CardContext) -> Result<()> {
@@ -712,31 +1627,57 @@ impl Pile {
             return Err(anyhow!("Pile is full"));
         }
         let card = context.get_card(card_index as usize)?;
+        if card.is_wild() {
+            return Ok(());
+        }
 
-        if !self.cards.is_empty() {
-            let prev_card = context.get_card(self.cards[self.cards.len() - 1] as usize)?;
+        if let Some((prev_card, top_number, top_gender)) = self.post_pile_top(context)? {
             if card.color != self.color {
                 tracing::warn!("Card color {card:?} does not match pile color {prev_card:?}");
                 return Err(anyhow!(
                     "Card color {card:?} does not match pile color {prev_card:?}"
                 ));
             }
-            if card.number != (prev_card.number - 1) {
+            if card.gender == top_gender {
+                tracing::warn!("Card gender {card:?} does not alternate with {prev_card:?}");
+                return Err(anyhow!("Genders must alternate"));
+            }
+            if card.number as i64 != top_number - 1 {
                 tracing::warn!("Card number {card:?} does not match pile counter {prev_card:?}");
                 return Err(anyhow!("Card number does not match pile counter"));
             }
         }
         Ok(())
     }
+    ///The topmost normal card of a post pile, along with the number and gender that the top of the pile stands for.
+    /// Each wild card stacked above the normal card counts as the next card down. Returns `None` if the pile holds no normal card.
+    fn post_pile_top(&self, context: &CardContext) -> Result<Option<(Card, i64, Gender)>> {
+        for (depth, card_index) in self.cards.iter().rev().enumerate() {
+            let card = *context.get_card(*card_index as usize)?;
+            if card.is_wild() {
+                continue;
+            }
+            let gender = match (card.gender, depth % 2) {
+                (gender, 0) => gender,
+                (Gender::Boy, _) => Gender::Girl,
+                (Gender::Girl, _) => Gender::Boy,
+            };
+            return Ok(Some((card, card.number as i64 - depth as i64, gender)));
+        }
+        Ok(None)
+    }
 }
 
-///The context holds all the created cards
+///The context holds all the created cards. The cards never change during a game, so clones of a game share them.
+#[derive(Clone)]
 pub struct CardContext {
-    cards: Vec<Card>,
+    cards: Arc<[Card]>,
 }
 impl CardContext {
     pub fn new(cards: Vec<Card>) -> CardContext {
-        CardContext { cards }
+        CardContext {
+            cards: cards.into(),
+        }
     }
     pub fn get_card(&self, index: usize) -> Result<&Card> {
         self.cards
@@ -747,6 +1688,7 @@ impl CardContext {
 
 ///The arena is the place where the players layout their cards (called "Dutch pile" in the original game). The aim is for the player
 /// to stack the cards in same-color sequential order.
+#[derive(Clone)]
 pub struct Arena {
     pub piles: Vec<Pile>,
 }
@@ -763,7 +1705,7 @@ impl Arena {
     ) -> Result<()> {
         //add a card to a pile, or create a new one if the card number==1, in the case of a new pile the number must be 1
         let card = *context.get_card(card_index as usize)?;
-        if card.number == 1 {
+        if card.number == 1 && !card.is_wild() {
             self.piles
                 .push(Pile::from_vec(vec![card_index], card.color));
             tracing::info!("Added new pile with card {:?} to arena", card);
@@ -789,7 +1731,7 @@ impl Arena {
     ) -> Result<()> {
         //add a card to a pile, or create a new one if the card number==1, in the case of a new pile the number must be 1
         let card = *context.get_card(card_index as usize)?;
-        if card.number == 1 {
+        if card.number == 1 && !card.is_wild() {
             return Ok(());
         }
         let pile = self
@@ -800,7 +1742,7 @@ impl Arena {
             return Err(anyhow!("Pile is full"));
         }
 
-        if card.color != pile.color {
+        if card.color != pile.color && !card.is_wild() {
             return Err(anyhow!(
                 "Card color {card:?} does not match pile color {pile:?}",
                 pile = pile.color
@@ -819,6 +1761,7 @@ impl Default for Arena {
 
 ///The player hand contains two list of cards, one that the player is currently holding, and the other a stack of available cards.
 /// They draw some amount of cards from their hand (3 usually), and then adds them to the available cards. The player can only play into the arena from the available cards.
+#[derive(Clone)]
 pub struct PlayerHand {
     pub in_hand: Vec<u32>,
     pub available_to_play: Vec<u32>,
@@ -890,6 +1833,7 @@ impl PlayerHand {
 ///the post pile is a set of 3 or 5 piles.
 /// In a game of 3 or less players, there are usually 5 post piles, but in a game of 4 or more players, there are 3.
 /// Players can stack cards on the post pile, but it must go in descending order and the genders must swap
+#[derive(Clone)]
 pub struct PostPile {
     pub piles: Vec<Pile>,
 }
@@ -906,7 +1850,10 @@ impl PostPile {
         card_index: u32,
         context: &CardContext,
     ) -> Result<()> {
-        self.piles[pile_index as usize].add_post_card(card_index, context)
+        self.piles
+            .get_mut(pile_index as usize)
+            .ok_or_else(|| anyhow!("Pile index out of bounds"))?
+            .add_post_card(card_index, context)
     }
     pub fn verify_add_card(
         &self,
@@ -914,7 +1861,10 @@ impl PostPile {
         card_index: u32,
         context: &CardContext,
     ) -> Result<()> {
-        self.piles[pile_index as usize].verify_add_post_card(card_index, context)
+        self.piles
+            .get(pile_index as usize)
+            .ok_or_else(|| anyhow!("Pile index out of bounds"))?
+            .verify_add_post_card(card_index, context)
     }
     ///Plays the top card from the post pile.
     pub fn play(&mut self, pile_index: u32) -> Result<u32> {
@@ -941,6 +1891,17 @@ impl PostPile {
     pub fn clear(&mut self) {
         self.piles.clear();
     }
+    pub fn to_proto(&self) -> proto::PostPile {
+        proto::PostPile {
+            piles: self
+                .piles
+                .iter()
+                .map(|p| proto::Pile {
+                    cards: p.cards.clone(),
+                })
+                .collect(),
+        }
+    }
 }
 
 impl Default for PostPile {
@@ -950,6 +1911,7 @@ impl Default for PostPile {
 }
 
 ///The BlitzPile is a pile of 10 cards dealt from the players main hand at the start of the game. If the player gets rid of all the cards in the blitz pile, the round ends.
+#[derive(Clone)]
 pub struct BlitzPile {
     pub cards: Vec<u32>,
 }
@@ -969,7 +1931,7 @@ impl BlitzPile {
         if self.cards.is_empty() {
             return Err(anyhow!("Blitz pile is empty"));
         }
-        return Ok(self.cards[self.cards.len() - 1]);
+        Ok(self.cards[self.cards.len() - 1])
     }
     pub fn can_call_blitz(&self) -> bool {
         self.cards.is_empty()
@@ -995,21 +1957,31 @@ pub enum Gender {
     Girl = 1,
 }
 
-///Genearte all possible cards for this game given the player count.
-pub fn generate_all_card(players: u32) -> Vec<Card> {
+///Wild cards stand in for any number or color. Their own number, color and gender are meaningless.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum CardKind {
+    Normal = 0,
+    Wild = 1,
+}
+
+///Genearte all possible cards for this game given the player count. Each player's 40 cards are followed by their wild cards.
+pub fn generate_all_card(players: u32, wild_cards_per_player: u32) -> Vec<Card> {
     let colors = [Color::Red, Color::Blue, Color::Green, Color::Yellow];
+    let deck_size = 40 + wild_cards_per_player;
     let mut cards = vec![
         Card {
             player_id: 0,
             number: 0,
             color: Color::Red,
-            gender: Gender::Boy
+            gender: Gender::Boy,
+            kind: CardKind::Normal,
         };
-        40 * players as usize
+        (deck_size * players) as usize
     ];
     for player in 0..players {
         for n in 0..40 {
-            cards[(n + 40 * player) as usize] = Card {
+            cards[(n + deck_size * player) as usize] = Card {
                 player_id: player,
                 number: (n % 10 + 1),
                 color: colors[((n) / 10) as usize],
@@ -1018,6 +1990,16 @@ pub fn generate_all_card(players: u32) -> Vec<Card> {
                     1 => Gender::Girl,
                     _ => unreachable!(),
                 },
+                kind: CardKind::Normal,
+            };
+        }
+        for n in 40..deck_size {
+            cards[(n + deck_size * player) as usize] = Card {
+                player_id: player,
+                number: 0,
+                color: Color::Red,
+                gender: Gender::Boy,
+                kind: CardKind::Wild,
             };
         }
     }
@@ -0,0 +1,191 @@
+//!Validation of [`proto::GamePrefs`], so that bad prefs are reported back to the players instead of producing a broken game.
+
+use std::fmt::Display;
+
+use crate::proto;
+
+///The most players that can sit at one game.
+pub const MAX_PLAYERS: u32 = 8;
+///The most wild cards that can be added to each player's deck.
+pub const MAX_WILD_CARDS_PER_PLAYER: u32 = 10;
+///The most post piles a player can have: what is left of the largest deck once the blitz pile has been dealt.
+pub const MAX_POST_PILE_SIZE: u32 = 40 + MAX_WILD_CARDS_PER_PLAYER - 10;
+
+///A single invalid field, named as it is in the protobuf definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldViolation {
+    pub field: String,
+    pub description: String,
+}
+
+///Game prefs that a game cannot be created from. Names every invalid field, not just the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidPrefs {
+    pub violations: Vec<FieldViolation>,
+}
+impl InvalidPrefs {
+    pub fn new(field: impl Into<String>, description: impl Into<String>) -> Self {
+        InvalidPrefs {
+            violations: vec![FieldViolation {
+                field: field.into(),
+                description: description.into(),
+            }],
+        }
+    }
+    pub fn to_proto(&self) -> Vec<proto::FieldViolation> {
+        self.violations
+            .iter()
+            .map(|v| proto::FieldViolation {
+                field: v.field.clone(),
+                description: v.description.clone(),
+            })
+            .collect()
+    }
+}
+impl Display for InvalidPrefs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Invalid game prefs: ")?;
+        for (i, v) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{} {}", v.field, v.description)?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for InvalidPrefs {}
+impl From<InvalidPrefs> for tonic::Status {
+    fn from(e: InvalidPrefs) -> Self {
+        tonic::Status::invalid_argument(e.to_string())
+    }
+}
+
+///Checks that a game for `player_count` players can be created from `prefs`.
+pub fn validate(player_count: u32, prefs: &proto::GamePrefs) -> Result<(), InvalidPrefs> {
+    let mut violations = vec![];
+    let mut violation = |field: &str, description: String| {
+        violations.push(FieldViolation {
+            field: field.to_string(),
+            description,
+        })
+    };
+
+    let game_mode = proto::GameMode::try_from(prefs.game_mode);
+    if game_mode == Ok(proto::GameMode::Solo) {
+        if player_count != 1 {
+            violation(
+                "playerCount",
+                format!("must be 1 in a solo game, but is {player_count}"),
+            );
+        }
+    } else if !(2..=MAX_PLAYERS).contains(&player_count) {
+        violation(
+            "playerCount",
+            format!("must be between 2 and {MAX_PLAYERS}, but is {player_count}"),
+        );
+    }
+    if prefs.wild_cards_per_player > MAX_WILD_CARDS_PER_PLAYER {
+        violation(
+            "wildCardsPerPlayer",
+            format!(
+                "must be at most {MAX_WILD_CARDS_PER_PLAYER}, but is {}",
+                prefs.wild_cards_per_player
+            ),
+        );
+    }
+    //what is left of the deck once the blitz pile has been dealt
+    let max_post_piles = 40 + prefs.wild_cards_per_player.min(MAX_WILD_CARDS_PER_PLAYER) - 10;
+    if !(1..=max_post_piles).contains(&prefs.post_pile_size) {
+        violation(
+            "postPileSize",
+            format!(
+                "must be between 1 and {max_post_piles}, but is {}",
+                prefs.post_pile_size
+            ),
+        );
+    }
+    let hand_size = max_post_piles.saturating_sub(prefs.post_pile_size).max(1);
+    if !(1..=hand_size).contains(&prefs.draw_rate) {
+        violation(
+            "drawRate",
+            format!(
+                "must be between 1 and the hand size of {hand_size}, but is {}",
+                prefs.draw_rate
+            ),
+        );
+    }
+    for (i, step) in prefs.draw_rate_schedule.iter().enumerate() {
+        if !(1..=hand_size).contains(&step.draw_rate) {
+            violation(
+                &format!("drawRateSchedule[{i}].drawRate"),
+                format!(
+                    "must be between 1 and the hand size of {hand_size}, but is {}",
+                    step.draw_rate
+                ),
+            );
+        }
+    }
+    if proto::PostRefillRule::try_from(prefs.post_refill_rule).is_err() {
+        violation(
+            "postRefillRule",
+            format!("{} is not a known refill rule", prefs.post_refill_rule),
+        );
+    }
+    if game_mode.is_err() {
+        violation(
+            "gameMode",
+            format!("{} is not a known game mode", prefs.game_mode),
+        );
+    }
+    //only classic games end by the win condition, but it must still be a known one
+    let classic = matches!(
+        game_mode,
+        Ok(proto::GameMode::Classic | proto::GameMode::Practice)
+    );
+    match proto::WinCondition::try_from(prefs.win_condition) {
+        Ok(proto::WinCondition::ScoreToWin) if classic && prefs.score_to_win == 0 => {
+            violation("scoreToWin", "must be greater than 0".to_string())
+        }
+        Ok(proto::WinCondition::FixedRounds) if classic && prefs.round_limit == 0 => {
+            violation("roundLimit", "must be greater than 0".to_string())
+        }
+        Ok(proto::WinCondition::RoundWins) if classic && prefs.round_wins_to_win == 0 => {
+            violation("roundWinsToWin", "must be greater than 0".to_string())
+        }
+        Ok(proto::WinCondition::TimeLimit) if classic && prefs.time_limit_seconds == 0 => {
+            violation("timeLimitSeconds", "must be greater than 0".to_string())
+        }
+        Ok(_) => {}
+        Err(_) => violation(
+            "winCondition",
+            format!("{} is not a known win condition", prefs.win_condition),
+        ),
+    }
+    if game_mode == Ok(proto::GameMode::Cooperative) {
+        if prefs.time_limit_seconds == 0 {
+            violation(
+                "timeLimitSeconds",
+                "must be greater than 0 in a cooperative game".to_string(),
+            );
+        }
+        //both counts come from the client, so they are clamped to their limits, which were checked above
+        let wild_cards = prefs.wild_cards_per_player.min(MAX_WILD_CARDS_PER_PLAYER);
+        let total_cards = player_count.min(MAX_PLAYERS) * (40 + wild_cards);
+        if prefs.cooperative_arena_goal > total_cards {
+            violation(
+                "cooperativeArenaGoal",
+                format!(
+                    "must be at most the {total_cards} cards in play, but is {}",
+                    prefs.cooperative_arena_goal
+                ),
+            );
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidPrefs { violations })
+    }
+}
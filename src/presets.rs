@@ -0,0 +1,72 @@
+//!Named rule presets. Each preset is a complete set of [`proto::GamePrefs`], so that players can pick a style of game without knowing what each setting does.
+
+use crate::proto;
+
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub draw_rate: u32,
+    pub post_pile_size: u32,
+    pub score_to_win: u32,
+    pub blitz_deduction: u32,
+}
+
+impl Preset {
+    ///The full game prefs for this preset. Every setting not named by the preset keeps its default.
+    pub fn prefs(&self) -> proto::GamePrefs {
+        proto::GamePrefs {
+            draw_rate: self.draw_rate,
+            post_pile_size: self.post_pile_size,
+            score_to_win: self.score_to_win,
+            blitz_deduction: self.blitz_deduction,
+            ..Default::default()
+        }
+    }
+    pub fn to_proto(&self) -> proto::RulePreset {
+        proto::RulePreset {
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+            prefs: Some(self.prefs()),
+        }
+    }
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "classic",
+        description: "The standard rules: draw three, three post piles, first to 72 points",
+        draw_rate: 3,
+        post_pile_size: 3,
+        score_to_win: 72,
+        blitz_deduction: 10,
+    },
+    Preset {
+        name: "quick",
+        description: "A short game. Five post piles make it easier to play cards, and 30 points wins",
+        draw_rate: 3,
+        post_pile_size: 5,
+        score_to_win: 30,
+        blitz_deduction: 10,
+    },
+    Preset {
+        name: "marathon",
+        description: "The standard rules, played to 150 points",
+        draw_rate: 3,
+        post_pile_size: 3,
+        score_to_win: 150,
+        blitz_deduction: 10,
+    },
+    Preset {
+        name: "kids",
+        description: "Draw one card at a time with five post piles, first to 40 points, and no deduction for missing a blitz",
+        draw_rate: 1,
+        post_pile_size: 5,
+        score_to_win: 40,
+        blitz_deduction: 0,
+    },
+];
+
+///Looks up a preset by name, ignoring case.
+pub fn get(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
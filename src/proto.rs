@@ -9,6 +9,8 @@ pub struct Card {
     pub color: i32,
     #[prost(enumeration = "Gender", tag = "4")]
     pub gender: i32,
+    #[prost(enumeration = "CardKind", tag = "5")]
+    pub kind: i32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -40,6 +42,25 @@ pub struct PlayerCards {
     #[prost(uint32, repeated, tag = "3")]
     pub blitz_pile: ::prost::alloc::vec::Vec<u32>,
 }
+/// The position of a game, for clients that run the rules themselves
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GameSnapshot {
+    #[prost(uint32, tag = "1")]
+    pub round: u32,
+    #[prost(uint32, tag = "2")]
+    pub draw_rate: u32,
+    /// Indexed by player id
+    #[prost(message, repeated, tag = "3")]
+    pub players: ::prost::alloc::vec::Vec<PlayerCards>,
+    #[prost(message, repeated, tag = "4")]
+    pub arena: ::prost::alloc::vec::Vec<Pile>,
+    /// Indexed by player id
+    #[prost(int32, repeated, tag = "5")]
+    pub total_scores: ::prost::alloc::vec::Vec<i32>,
+    #[prost(bool, tag = "6")]
+    pub is_game_over: bool,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Player {
@@ -93,6 +114,18 @@ pub struct GlobalDeck {
     #[prost(message, repeated, tag = "1")]
     pub cards: ::prost::alloc::vec::Vec<Card>,
 }
+/// A draw rate that the server applies automatically. A step applies from the start of fromRound (counting from 0) onwards,
+/// or, when afterSeconds is set, once that many seconds of each such round have passed. The most specific applicable step wins
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrawRateStep {
+    #[prost(uint32, tag = "1")]
+    pub from_round: u32,
+    #[prost(uint32, optional, tag = "2")]
+    pub after_seconds: ::core::option::Option<u32>,
+    #[prost(uint32, tag = "3")]
+    pub draw_rate: u32,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GamePrefs {
@@ -104,6 +137,51 @@ pub struct GamePrefs {
     pub score_to_win: u32,
     #[prost(uint32, tag = "4")]
     pub blitz_deduction: u32,
+    /// End the round as soon as any blitz pile is emptied, without waiting for that player to call blitz
+    #[prost(bool, tag = "5")]
+    pub auto_end_round: bool,
+    #[prost(enumeration = "PostRefillRule", tag = "6")]
+    pub post_refill_rule: i32,
+    /// Points deducted from a player's round score for every rejected arena play
+    #[prost(uint32, tag = "7")]
+    pub misplay_penalty: u32,
+    /// How long, in milliseconds, a player is prevented from playing after a rejected arena play
+    #[prost(uint32, tag = "8")]
+    pub misplay_lockout_ms: u32,
+    #[prost(message, repeated, tag = "9")]
+    pub draw_rate_schedule: ::prost::alloc::vec::Vec<DrawRateStep>,
+    /// Number of wild cards added to each player's deck
+    #[prost(uint32, tag = "10")]
+    pub wild_cards_per_player: u32,
+    /// Points added to (or, when negative, deducted from) a player's round score for each of their wild cards in the arena
+    #[prost(int32, tag = "11")]
+    pub wild_card_bonus: i32,
+    /// Points for each of a player's cards in the arena. Defaults to 1
+    #[prost(int32, optional, tag = "12")]
+    pub arena_card_points: ::core::option::Option<i32>,
+    /// Points for each card left in a player's blitz pile at the end of the round. Defaults to -2
+    #[prost(int32, optional, tag = "13")]
+    pub blitz_card_points: ::core::option::Option<i32>,
+    /// Points for each arena pile a player completes by playing its 10
+    #[prost(int32, tag = "14")]
+    pub completed_pile_bonus: i32,
+    /// Points for each player whose blitz pile is empty when the round is scored
+    #[prost(int32, tag = "15")]
+    pub blitz_bonus: i32,
+    #[prost(enumeration = "WinCondition", tag = "16")]
+    pub win_condition: i32,
+    #[prost(uint32, tag = "17")]
+    pub round_limit: u32,
+    #[prost(uint32, tag = "18")]
+    pub round_wins_to_win: u32,
+    #[prost(uint32, tag = "19")]
+    pub time_limit_seconds: u32,
+    #[prost(enumeration = "GameMode", tag = "20")]
+    pub game_mode: i32,
+    /// In a cooperative game, the number of cards the team must get into the arena.
+    /// When 0, the goal is instead to empty every player's blitz pile
+    #[prost(uint32, tag = "21")]
+    pub cooperative_arena_goal: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -112,6 +190,30 @@ pub struct StartGameEvent {
     pub player: ::core::option::Option<Player>,
     #[prost(message, optional, tag = "2")]
     pub prefs: ::core::option::Option<GamePrefs>,
+    /// The name of a rule preset to play with. When set, prefs must be left empty
+    #[prost(string, tag = "3")]
+    pub preset: ::prost::alloc::string::String,
+    /// Play today's daily challenge: a solo game dealt the same way for everyone. When set, prefs and preset must be left empty,
+    /// and the session must have a single player
+    #[prost(bool, tag = "4")]
+    pub daily_challenge: bool,
+}
+/// A named, complete set of game prefs
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RulePreset {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub prefs: ::core::option::Option<GamePrefs>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RulePresets {
+    #[prost(message, repeated, tag = "1")]
+    pub presets: ::prost::alloc::vec::Vec<RulePreset>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -154,7 +256,7 @@ pub struct ClientEvent {
     /// The id of the player who made this event
     #[prost(uint32, tag = "4")]
     pub player_id: u32,
-    #[prost(oneof = "client_event::Event", tags = "1, 2, 5, 6, 7, 8")]
+    #[prost(oneof = "client_event::Event", tags = "1, 2, 5, 6, 7, 8, 9, 10")]
     pub event: ::core::option::Option<client_event::Event>,
 }
 /// Nested message and enum types in `ClientEvent`.
@@ -176,10 +278,85 @@ pub mod client_event {
         ChangeDrawRate(super::ChangeDrawRateEvent),
         #[prost(message, tag = "8")]
         Acknowledge(super::Acknowledge),
+        /// Take back this player's most recent play. Only allowed in practice and solo games
+        #[prost(message, tag = "9")]
+        Undo(super::UndoEvent),
+        #[prost(message, tag = "10")]
+        Hint(super::HintRequest),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UndoEvent {}
+/// Ask for the moves this player can make, best first. Only allowed in practice and solo games
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HintRequest {}
+/// A legal move, and why it is worth making
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Hint {
+    #[prost(message, optional, tag = "1")]
+    pub play: ::core::option::Option<Play>,
+    /// Higher is better
+    #[prost(int32, tag = "2")]
+    pub score: i32,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// An arena or blitz play that a player left unmade for several seconds
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MissedOpportunity {
+    #[prost(uint32, tag = "1")]
+    pub round: u32,
+    /// Milliseconds from the start of the game until the play became available
+    #[prost(uint32, tag = "2")]
+    pub at_ms: u32,
+    /// How long the play was available before the player made any move, or before it was no longer possible
+    #[prost(uint32, tag = "3")]
+    pub waited_ms: u32,
+    #[prost(message, optional, tag = "4")]
+    pub play: ::core::option::Option<Play>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlayerAnalysis {
+    #[prost(uint32, tag = "1")]
+    pub player_id: u32,
+    /// Cards the player got into the arena
+    #[prost(uint32, tag = "2")]
+    pub arena_cards: u32,
+    #[prost(float, tag = "3")]
+    pub cards_per_minute: f32,
+    /// Number of times the player drew from their hand
+    #[prost(uint32, tag = "4")]
+    pub transfers: u32,
+    /// Milliseconds between drawing from, or resetting, the hand and the player's next move
+    #[prost(uint32, tag = "5")]
+    pub cycling_ms: u32,
+    #[prost(message, repeated, tag = "6")]
+    pub missed_opportunities: ::prost::alloc::vec::Vec<MissedOpportunity>,
+}
+/// Sent to all players once the game is over
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GameAnalysis {
+    #[prost(message, repeated, tag = "1")]
+    pub players: ::prost::alloc::vec::Vec<PlayerAnalysis>,
+    /// Milliseconds from the start of each round until its last play, summed over all rounds
+    #[prost(uint32, tag = "2")]
+    pub played_ms: u32,
+}
+/// Sent only to the player who asked for hints
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HintEvent {
+    #[prost(message, repeated, tag = "1")]
+    pub hints: ::prost::alloc::vec::Vec<Hint>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ArenaStateChange {
     /// The action that was taken (add or remove)
     #[prost(enumeration = "StateChangeAction", tag = "1")]
@@ -205,6 +382,15 @@ pub struct PlayerStateChange {
     #[prost(message, optional, tag = "9")]
     pub new_post_pile: ::core::option::Option<PostPile>,
 }
+/// A field of a request that was rejected, and why
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldViolation {
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+}
 /// Sent after the server receives a client event or vice versa. This is used to acknowledge that the server received the event and finished processing it
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -216,6 +402,9 @@ pub struct Acknowledge {
     pub acknowledgement_type: i32,
     #[prost(string, tag = "3")]
     pub message: ::prost::alloc::string::String,
+    /// When a request is rejected because of invalid fields, names each of them
+    #[prost(message, repeated, tag = "4")]
+    pub field_violations: ::prost::alloc::vec::Vec<FieldViolation>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -226,6 +415,54 @@ pub struct ServerRequestStartGameEvent {
     pub global_deck: ::core::option::Option<GlobalDeck>,
     #[prost(message, repeated, tag = "3")]
     pub player_cards: ::prost::alloc::vec::Vec<PlayerCards>,
+    /// Set in multi-table games, where the deck and the player cards are those of the player's table
+    #[prost(message, optional, tag = "4")]
+    pub table_seat: ::core::option::Option<TableSeat>,
+}
+/// Sessions with more than 8 players are split into tables, each playing its own game. After every round, once all tables have
+/// finished it, the players are reseated by their combined standings: the leaders at the first table, and so on
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TableSeat {
+    #[prost(uint32, tag = "1")]
+    pub table: u32,
+    /// The player's id in the table's game. Plays, piles and scores at the table name players by seat rather than by playerGameId
+    #[prost(uint32, tag = "2")]
+    pub seat: u32,
+    /// The playerGameId of the player in each seat
+    #[prost(uint32, repeated, tag = "3")]
+    pub players: ::prost::alloc::vec::Vec<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TableRoundScores {
+    /// The playerGameId of the player in each seat
+    #[prost(uint32, repeated, tag = "1")]
+    pub players: ::prost::alloc::vec::Vec<u32>,
+    /// The scores of the round, with players named by seat
+    #[prost(message, repeated, tag = "2")]
+    pub scores: ::prost::alloc::vec::Vec<PlayerRoundScore>,
+}
+/// Sent to every player of a multi-table game once all tables have finished the round. Unless the game is over, each player is then
+/// sent a requestStartGame event for the table they have been reseated at
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TablesRoundOverEvent {
+    #[prost(uint32, tag = "1")]
+    pub round: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub tables: ::prost::alloc::vec::Vec<TableRoundScores>,
+    /// The combined scores of every player, with players named by playerGameId
+    #[prost(message, repeated, tag = "3")]
+    pub scores: ::prost::alloc::vec::Vec<PlayerRoundScore>,
+    /// playerGameIds ordered from first to last place by combined score
+    #[prost(uint32, repeated, tag = "4")]
+    pub standings: ::prost::alloc::vec::Vec<u32>,
+    #[prost(bool, tag = "5")]
+    pub is_game_over: bool,
+    /// Set once the game is over
+    #[prost(uint32, optional, tag = "6")]
+    pub winner: ::core::option::Option<u32>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -242,12 +479,111 @@ pub struct GamePlayError {
     #[prost(string, tag = "1")]
     pub message: ::prost::alloc::string::String,
 }
+/// A player's score for one round, along with each of the components it is made up of
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlayerRoundScore {
+    #[prost(uint32, tag = "1")]
+    pub player_id: u32,
+    #[prost(int32, tag = "2")]
+    pub round_score: i32,
+    #[prost(int32, tag = "3")]
+    pub total_score: i32,
+    #[prost(uint32, tag = "4")]
+    pub arena_cards: u32,
+    #[prost(uint32, tag = "5")]
+    pub blitz_cards_left: u32,
+    #[prost(int32, tag = "6")]
+    pub arena_points: i32,
+    #[prost(int32, tag = "7")]
+    pub blitz_points: i32,
+    #[prost(int32, tag = "8")]
+    pub wild_card_points: i32,
+    #[prost(int32, tag = "9")]
+    pub completed_pile_points: i32,
+    #[prost(int32, tag = "10")]
+    pub blitz_bonus: i32,
+    #[prost(int32, tag = "11")]
+    pub penalty_points: i32,
+    /// Whether the player has been eliminated, in this round or an earlier one
+    #[prost(bool, tag = "12")]
+    pub is_eliminated: bool,
+}
+/// Sent to all players when the server ends a round. Unless the game is over, it also holds the cards dealt for the next round
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RoundOverEvent {
+    #[prost(uint32, tag = "1")]
+    pub round: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub scores: ::prost::alloc::vec::Vec<PlayerRoundScore>,
+    #[prost(bool, tag = "3")]
+    pub is_game_over: bool,
+    #[prost(message, repeated, tag = "4")]
+    pub player_cards: ::prost::alloc::vec::Vec<PlayerCards>,
+    /// Player ids ordered from first to last place
+    #[prost(uint32, repeated, tag = "5")]
+    pub standings: ::prost::alloc::vec::Vec<u32>,
+    /// Set once the game is over
+    #[prost(uint32, optional, tag = "6")]
+    pub winner: ::core::option::Option<u32>,
+    /// The player eliminated at the end of this round, in elimination mode
+    #[prost(uint32, optional, tag = "7")]
+    pub eliminated: ::core::option::Option<u32>,
+    /// Set at the end of a cooperative game: whether the team reached its goal in time
+    #[prost(bool, optional, tag = "8")]
+    pub team_won: ::core::option::Option<bool>,
+    /// In a cooperative game, the team's combined score
+    #[prost(int32, optional, tag = "9")]
+    pub team_score: ::core::option::Option<i32>,
+    /// Set at the end of a solo game
+    #[prost(message, optional, tag = "10")]
+    pub solo_result: ::core::option::Option<SoloResult>,
+}
+/// How a solo run went
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SoloResult {
+    /// Whether the blitz pile was emptied, rather than the run being given up by calling blitz
+    #[prost(bool, tag = "1")]
+    pub completed: bool,
+    /// Milliseconds from the start of the round until it ended
+    #[prost(uint32, tag = "2")]
+    pub completion_ms: u32,
+    /// Number of plays accepted during the round
+    #[prost(uint32, tag = "3")]
+    pub moves: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyLeaderboardRq {
+    /// Days since the Unix epoch, in UTC. Defaults to today
+    #[prost(uint32, optional, tag = "1")]
+    pub day: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyResult {
+    #[prost(string, tag = "1")]
+    pub username: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub result: ::core::option::Option<SoloResult>,
+}
+/// Runs are ranked by whether they were completed, then by the fastest completion time, then by the fewest moves
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyLeaderboard {
+    #[prost(uint32, tag = "1")]
+    pub day: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub results: ::prost::alloc::vec::Vec<DailyResult>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ServerEvent {
     #[prost(uint32, tag = "8")]
     pub event_id: u32,
-    #[prost(oneof = "server_event::Event", tags = "1, 3, 5, 4, 7, 6, 9")]
+    #[prost(oneof = "server_event::Event", tags = "1, 3, 5, 4, 7, 6, 9, 10, 11, 12, 13")]
     pub event: ::core::option::Option<server_event::Event>,
 }
 /// Nested message and enum types in `ServerEvent`.
@@ -269,6 +605,14 @@ pub mod server_event {
         ConfirmGameStart(super::ServerRequestStartGameEvent),
         #[prost(message, tag = "9")]
         GamePlayError(super::GamePlayError),
+        #[prost(message, tag = "10")]
+        RoundOver(super::RoundOverEvent),
+        #[prost(message, tag = "11")]
+        Hints(super::HintEvent),
+        #[prost(message, tag = "12")]
+        Analysis(super::GameAnalysis),
+        #[prost(message, tag = "13")]
+        TablesRoundOver(super::TablesRoundOverEvent),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -302,6 +646,8 @@ pub struct PlayerPlay {
     pub play_type: i32,
     #[prost(uint32, optional, tag = "2")]
     pub post_index: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub to_post_index: ::core::option::Option<u32>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -404,6 +750,33 @@ impl Gender {
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum CardKind {
+    Normal = 0,
+    /// Stands in for any number or colour. Wild cards have no number, colour or gender of their own
+    Wild = 1,
+}
+impl CardKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CardKind::Normal => "NORMAL",
+            CardKind::Wild => "WILD",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NORMAL" => Some(Self::Normal),
+            "WILD" => Some(Self::Wild),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum EAcknowledgementType {
     Accepted = 0,
     Rejected = 1,
@@ -428,6 +801,120 @@ impl EAcknowledgementType {
         }
     }
 }
+/// How empty post piles are refilled from the blitz pile
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PostRefillRule {
+    /// Players refill empty post piles themselves, whenever they like
+    ManualRefill = 0,
+    /// The server immediately refills an empty post pile with the top card of the blitz pile
+    AutomaticRefill = 1,
+    /// Players must refill an empty post pile from the blitz pile before making any other move
+    ForcedRefill = 2,
+}
+impl PostRefillRule {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PostRefillRule::ManualRefill => "MANUAL_REFILL",
+            PostRefillRule::AutomaticRefill => "AUTOMATIC_REFILL",
+            PostRefillRule::ForcedRefill => "FORCED_REFILL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "MANUAL_REFILL" => Some(Self::ManualRefill),
+            "AUTOMATIC_REFILL" => Some(Self::AutomaticRefill),
+            "FORCED_REFILL" => Some(Self::ForcedRefill),
+            _ => None,
+        }
+    }
+}
+/// How the end of the game is decided. Whatever the condition, ties are broken by the fewest points lost to cards left in blitz piles,
+/// then by the most cards played into the arena, then by the lowest player id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WinCondition {
+    /// The game ends once any player's total reaches scoreToWin. The highest total wins
+    ScoreToWin = 0,
+    /// The game ends after roundLimit rounds. The highest total wins
+    FixedRounds = 1,
+    /// The first player to win roundWinsToWin rounds, by having the best score in the round, wins
+    RoundWins = 2,
+    /// The game ends once timeLimitSeconds have passed, and the round in progress is scored. The highest total wins
+    TimeLimit = 3,
+}
+impl WinCondition {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            WinCondition::ScoreToWin => "SCORE_TO_WIN",
+            WinCondition::FixedRounds => "FIXED_ROUNDS",
+            WinCondition::RoundWins => "ROUND_WINS",
+            WinCondition::TimeLimit => "TIME_LIMIT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SCORE_TO_WIN" => Some(Self::ScoreToWin),
+            "FIXED_ROUNDS" => Some(Self::FixedRounds),
+            "ROUND_WINS" => Some(Self::RoundWins),
+            "TIME_LIMIT" => Some(Self::TimeLimit),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum GameMode {
+    /// Everyone plays every round until the win condition is met
+    Classic = 0,
+    /// After each round the remaining player with the lowest total is eliminated and stays on as a spectator.
+    /// The win condition is ignored: the last player left wins
+    Elimination = 1,
+    /// All players work together to reach one goal before timeLimitSeconds run out. The game is a single round,
+    /// which ends as soon as the goal is reached, when time runs out, or when blitz is called. The win condition is ignored
+    Cooperative = 2,
+    /// A single player races to empty their blitz pile. The game is a single round, which ends as soon as the blitz pile
+    /// is empty or when blitz is called. The win condition is ignored
+    Solo = 3,
+    /// Played like CLASSIC, but players may undo their plays
+    Practice = 4,
+}
+impl GameMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "CLASSIC",
+            GameMode::Elimination => "ELIMINATION",
+            GameMode::Cooperative => "COOPERATIVE",
+            GameMode::Solo => "SOLO",
+            GameMode::Practice => "PRACTICE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CLASSIC" => Some(Self::Classic),
+            "ELIMINATION" => Some(Self::Elimination),
+            "COOPERATIVE" => Some(Self::Cooperative),
+            "SOLO" => Some(Self::Solo),
+            "PRACTICE" => Some(Self::Practice),
+            _ => None,
+        }
+    }
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ClientGameStateAction {
@@ -591,6 +1078,8 @@ pub enum PlayerPlayType {
     AvailableHandToPost = 1,
     TransferToAvailableHand = 2,
     ResetHand = 3,
+    /// Move the top card of the post pile at postIndex onto the post pile at toPostIndex
+    PostToPost = 4,
 }
 impl PlayerPlayType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -603,6 +1092,7 @@ impl PlayerPlayType {
             PlayerPlayType::AvailableHandToPost => "AVAILABLE_HAND_TO_POST",
             PlayerPlayType::TransferToAvailableHand => "TRANSFER_TO_AVAILABLE_HAND",
             PlayerPlayType::ResetHand => "RESET_HAND",
+            PlayerPlayType::PostToPost => "POST_TO_POST",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -612,6 +1102,7 @@ impl PlayerPlayType {
             "AVAILABLE_HAND_TO_POST" => Some(Self::AvailableHandToPost),
             "TRANSFER_TO_AVAILABLE_HAND" => Some(Self::TransferToAvailableHand),
             "RESET_HAND" => Some(Self::ResetHand),
+            "POST_TO_POST" => Some(Self::PostToPost),
             _ => None,
         }
     }
@@ -815,6 +1306,55 @@ pub mod session_service_client {
                 .insert(GrpcMethod::new("proto.SessionService", "GetSession"));
             self.inner.unary(req, path, codec).await
         }
+        /// List the named rule presets that a game can be started with
+        pub async fn get_rule_presets(
+            &mut self,
+            request: impl tonic::IntoRequest<()>,
+        ) -> std::result::Result<tonic::Response<super::RulePresets>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/proto.SessionService/GetRulePresets",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("proto.SessionService", "GetRulePresets"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// The best run of each player at a day's daily challenge, best first. Runs are stored by the server and kept across restarts
+        pub async fn get_daily_leaderboard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DailyLeaderboardRq>,
+        ) -> std::result::Result<
+            tonic::Response<super::DailyLeaderboard>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/proto.SessionService/GetDailyLeaderboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("proto.SessionService", "GetDailyLeaderboard"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated client implementations.
@@ -1095,6 +1635,19 @@ pub mod session_service_server {
             &self,
             request: tonic::Request<super::GetSessionRq>,
         ) -> std::result::Result<tonic::Response<super::Session>, tonic::Status>;
+        /// List the named rule presets that a game can be started with
+        async fn get_rule_presets(
+            &self,
+            request: tonic::Request<()>,
+        ) -> std::result::Result<tonic::Response<super::RulePresets>, tonic::Status>;
+        /// The best run of each player at a day's daily challenge, best first. Runs are stored by the server and kept across restarts
+        async fn get_daily_leaderboard(
+            &self,
+            request: tonic::Request<super::DailyLeaderboardRq>,
+        ) -> std::result::Result<
+            tonic::Response<super::DailyLeaderboard>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct SessionServiceServer<T: SessionService> {
@@ -1399,6 +1952,98 @@ pub mod session_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/proto.SessionService/GetRulePresets" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetRulePresetsSvc<T: SessionService>(pub Arc<T>);
+                    impl<T: SessionService> tonic::server::UnaryService<()>
+                    for GetRulePresetsSvc<T> {
+                        type Response = super::RulePresets;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(&mut self, request: tonic::Request<()>) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SessionService>::get_rule_presets(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetRulePresetsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/proto.SessionService/GetDailyLeaderboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetDailyLeaderboardSvc<T: SessionService>(pub Arc<T>);
+                    impl<
+                        T: SessionService,
+                    > tonic::server::UnaryService<super::DailyLeaderboardRq>
+                    for GetDailyLeaderboardSvc<T> {
+                        type Response = super::DailyLeaderboard;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DailyLeaderboardRq>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SessionService>::get_daily_leaderboard(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetDailyLeaderboardSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
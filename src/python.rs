@@ -0,0 +1,373 @@
+//!Python bindings for the game engine, built with the `python` feature. The extension module is built from the `blitz-python`
+//! crate in `python/`, which maturin picks up from `pyproject.toml`:
+//! ```sh
+//! maturin develop --release
+//! ```
+//! ```python
+//! import blitz
+//! game = blitz.GameStateBuilder().with_player_count(3).with_seed(7).build()
+//! saved = game.snapshot()
+//! for play in game.legal_moves(0):
+//!     game.make_play(play)
+//!     game.restore(saved)
+//! ```
+//! Cards are named as in [`crate::scenario`], such as `R5`, or `1:B3` for a card that belongs to player 1.
+
+use std::time::Duration;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::scenario::{card_name, Scenario};
+use crate::{proto, Action, ArenaAction, PlayerAction};
+
+fn value_error(e: anyhow::Error) -> PyErr {
+    PyValueError::new_err(format!("{e:#}"))
+}
+
+#[pyclass(name = "GameStateBuilder")]
+#[derive(Clone)]
+pub struct PyGameStateBuilder(crate::GameStateBuilder);
+
+#[pymethods]
+impl PyGameStateBuilder {
+    #[new]
+    fn new() -> Self {
+        Self(crate::GameStateBuilder::new())
+    }
+    fn with_player_count(&self, player_count: u32) -> Self {
+        Self(self.0.clone().with_player_count(player_count))
+    }
+    fn with_draw_rate(&self, draw_rate: u32) -> Self {
+        Self(self.0.clone().with_draw_rate(draw_rate))
+    }
+    fn with_post_pile_size(&self, post_pile_size: u32) -> Self {
+        Self(self.0.clone().with_post_pile_size(post_pile_size))
+    }
+    fn with_score_to_win(&self, score_to_win: u32) -> Self {
+        Self(self.0.clone().with_score_to_win(score_to_win))
+    }
+    fn with_blitz_deduction(&self, blitz_deduction: u32) -> Self {
+        Self(self.0.clone().with_blitz_deduction(blitz_deduction))
+    }
+    fn with_auto_end_round(&self, auto_end_round: bool) -> Self {
+        Self(self.0.clone().with_auto_end_round(auto_end_round))
+    }
+    ///Takes the name of a post refill rule, such as `FORCED_REFILL`.
+    fn with_post_refill_rule(&self, post_refill_rule: &str) -> PyResult<Self> {
+        let post_refill_rule =
+            proto::PostRefillRule::from_str_name(post_refill_rule).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "{post_refill_rule} is not a known post refill rule"
+                ))
+            })?;
+        Ok(Self(self.0.clone().with_post_refill_rule(post_refill_rule)))
+    }
+    fn with_misplay_penalty(&self, misplay_penalty: u32) -> Self {
+        Self(self.0.clone().with_misplay_penalty(misplay_penalty))
+    }
+    fn with_misplay_lockout_ms(&self, misplay_lockout_ms: u64) -> Self {
+        Self(
+            self.0
+                .clone()
+                .with_misplay_lockout(Duration::from_millis(misplay_lockout_ms)),
+        )
+    }
+    ///Takes `(from_round, after_seconds, draw_rate)` tuples, where `after_seconds` may be `None` for a step that applies from the
+    /// start of the round.
+    fn with_draw_rate_schedule(&self, draw_rate_schedule: Vec<(u32, Option<u32>, u32)>) -> Self {
+        let draw_rate_schedule = draw_rate_schedule
+            .into_iter()
+            .map(
+                |(from_round, after_seconds, draw_rate)| proto::DrawRateStep {
+                    from_round,
+                    after_seconds,
+                    draw_rate,
+                },
+            )
+            .collect();
+        Self(self.0.clone().with_draw_rate_schedule(draw_rate_schedule))
+    }
+    fn with_wild_cards_per_player(&self, wild_cards_per_player: u32) -> Self {
+        Self(
+            self.0
+                .clone()
+                .with_wild_cards_per_player(wild_cards_per_player),
+        )
+    }
+    fn with_wild_card_bonus(&self, wild_card_bonus: i32) -> Self {
+        Self(self.0.clone().with_wild_card_bonus(wild_card_bonus))
+    }
+    fn with_arena_card_points(&self, arena_card_points: i32) -> Self {
+        Self(self.0.clone().with_arena_card_points(arena_card_points))
+    }
+    fn with_blitz_card_points(&self, blitz_card_points: i32) -> Self {
+        Self(self.0.clone().with_blitz_card_points(blitz_card_points))
+    }
+    fn with_completed_pile_bonus(&self, completed_pile_bonus: i32) -> Self {
+        Self(
+            self.0
+                .clone()
+                .with_completed_pile_bonus(completed_pile_bonus),
+        )
+    }
+    fn with_blitz_bonus(&self, blitz_bonus: i32) -> Self {
+        Self(self.0.clone().with_blitz_bonus(blitz_bonus))
+    }
+    ///Takes the name of a win condition, such as `FIXED_ROUNDS`.
+    fn with_win_condition(&self, win_condition: &str) -> PyResult<Self> {
+        let win_condition = proto::WinCondition::from_str_name(win_condition).ok_or_else(|| {
+            PyValueError::new_err(format!("{win_condition} is not a known win condition"))
+        })?;
+        Ok(Self(self.0.clone().with_win_condition(win_condition)))
+    }
+    fn with_round_limit(&self, round_limit: u32) -> Self {
+        Self(self.0.clone().with_round_limit(round_limit))
+    }
+    fn with_round_wins_to_win(&self, round_wins_to_win: u32) -> Self {
+        Self(self.0.clone().with_round_wins_to_win(round_wins_to_win))
+    }
+    fn with_time_limit_seconds(&self, time_limit_seconds: u64) -> Self {
+        Self(
+            self.0
+                .clone()
+                .with_time_limit(Duration::from_secs(time_limit_seconds)),
+        )
+    }
+    ///Takes the name of a game mode, such as `PRACTICE`.
+    fn with_game_mode(&self, game_mode: &str) -> PyResult<Self> {
+        let game_mode = proto::GameMode::from_str_name(game_mode).ok_or_else(|| {
+            PyValueError::new_err(format!("{game_mode} is not a known game mode"))
+        })?;
+        Ok(Self(self.0.clone().with_game_mode(game_mode)))
+    }
+    fn with_cooperative_arena_goal(&self, cooperative_arena_goal: u32) -> Self {
+        Self(
+            self.0
+                .clone()
+                .with_cooperative_arena_goal(cooperative_arena_goal),
+        )
+    }
+    fn with_seed(&self, seed: u64) -> Self {
+        Self(self.0.clone().with_seed(seed))
+    }
+    fn build(&self) -> PyResult<PyGameState> {
+        Ok(PyGameState(self.0.clone().build().map_err(value_error)?))
+    }
+}
+
+///A play, made with one of the static methods named after the [`Action`] it stands for.
+#[pyclass(name = "Play", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyPlay(crate::Play);
+
+#[pymethods]
+impl PyPlay {
+    #[staticmethod]
+    fn call_blitz(player: u32) -> Self {
+        Self::new(player, Action::CallBlitz(player))
+    }
+    #[staticmethod]
+    fn from_available_hand(player: u32, arena_pile: u32) -> Self {
+        Self::new(
+            player,
+            Action::Arena(ArenaAction::FromAvailableHand(arena_pile)),
+        )
+    }
+    #[staticmethod]
+    fn from_blitz(player: u32, arena_pile: u32) -> Self {
+        Self::new(player, Action::Arena(ArenaAction::FromBlitz(arena_pile)))
+    }
+    #[staticmethod]
+    fn from_post(player: u32, post_pile: u32, arena_pile: u32) -> Self {
+        Self::new(
+            player,
+            Action::Arena(ArenaAction::FromPost {
+                post_pile,
+                arena_pile,
+            }),
+        )
+    }
+    #[staticmethod]
+    fn blitz_to_post(player: u32, post_pile: u32) -> Self {
+        Self::new(player, Action::Player(PlayerAction::BlitzToPost(post_pile)))
+    }
+    #[staticmethod]
+    fn available_to_post(player: u32, post_pile: u32) -> Self {
+        Self::new(
+            player,
+            Action::Player(PlayerAction::AvailableToPost(post_pile)),
+        )
+    }
+    #[staticmethod]
+    fn transfer_to_available(player: u32) -> Self {
+        Self::new(player, Action::Player(PlayerAction::TransferToAvailable))
+    }
+    #[staticmethod]
+    fn reset_hand(player: u32) -> Self {
+        Self::new(player, Action::Player(PlayerAction::ResetHand))
+    }
+    #[staticmethod]
+    fn post_to_post(player: u32, from: u32, to: u32) -> Self {
+        Self::new(
+            player,
+            Action::Player(PlayerAction::PostToPost { from, to }),
+        )
+    }
+    #[getter]
+    fn player(&self) -> u32 {
+        self.0.player
+    }
+    fn __repr__(&self) -> String {
+        format!("Play(player={}, {:?})", self.0.player, self.0.play)
+    }
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0.player == other.0.player && self.0.play == other.0.play
+    }
+}
+impl PyPlay {
+    fn new(player: u32, play: Action) -> Self {
+        Self(crate::Play { player, play })
+    }
+}
+
+#[pyclass(name = "Scoreboard", frozen)]
+pub struct PyScoreboard(crate::Scoreboard);
+
+#[pymethods]
+impl PyScoreboard {
+    ///Each player's score in every round played so far.
+    #[getter]
+    fn scores(&self) -> Vec<Vec<i32>> {
+        self.0.scores.clone()
+    }
+    fn totals(&self) -> Vec<i32> {
+        self.0.get_totals()
+    }
+    fn round_wins(&self) -> Vec<u32> {
+        self.0.round_wins()
+    }
+}
+
+///A saved position, to be put back with `GameState.restore`.
+#[pyclass(name = "Snapshot", frozen)]
+pub struct PySnapshot(crate::GameState);
+
+#[pyclass(name = "GameState")]
+pub struct PyGameState(crate::GameState);
+
+#[pymethods]
+impl PyGameState {
+    ///Loads an exact position written in the [scenario](crate::scenario) format.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let scenario = Scenario::from_json(json).map_err(value_error)?;
+        Ok(Self(scenario.load().map_err(value_error)?))
+    }
+    ///Writes the position in the [scenario](crate::scenario) format.
+    fn to_json(&self) -> PyResult<String> {
+        Scenario::capture(&self.0).to_json().map_err(value_error)
+    }
+    fn snapshot(&self) -> PySnapshot {
+        PySnapshot(self.0.clone())
+    }
+    fn restore(&mut self, snapshot: &PySnapshot) {
+        self.0 = snapshot.0.clone();
+    }
+
+    fn legal_moves(&self, player: u32) -> Vec<PyPlay> {
+        self.0
+            .legal_moves(player)
+            .into_iter()
+            .map(|play| PyPlay::new(player, play))
+            .collect()
+    }
+    ///Raises `ValueError` if the play is not allowed, in which case nothing changes.
+    fn make_play(&mut self, play: &PyPlay) -> PyResult<()> {
+        self.0.make_play(play.0).map_err(value_error)?;
+        Ok(())
+    }
+    fn should_end_round(&self) -> bool {
+        self.0.should_end_round()
+    }
+    ///Scores the round and, unless the game is over, deals the next one. Returns each player's score for the round.
+    fn end_round(&mut self) -> PyResult<Vec<i32>> {
+        let round_over = self.0.end_round().map_err(value_error)?;
+        Ok(round_over.scores.iter().map(|s| s.round_score).collect())
+    }
+    fn is_game_over(&self) -> bool {
+        self.0.is_game_over()
+    }
+    #[getter]
+    fn round(&self) -> u32 {
+        self.0.round
+    }
+    #[getter]
+    fn player_count(&self) -> usize {
+        self.0.players.len()
+    }
+    #[getter]
+    fn scoreboard(&self) -> PyScoreboard {
+        PyScoreboard(self.0.scoreboard.clone())
+    }
+    fn standings(&self) -> Vec<u32> {
+        self.0.standings()
+    }
+    fn winner(&self) -> Option<u32> {
+        self.0.winner()
+    }
+
+    fn hand(&self, player: usize) -> PyResult<Vec<String>> {
+        let cards = &self.player(player)?.hand.in_hand;
+        Ok(self.names(Some(player as u32), cards))
+    }
+    ///The cards that have been drawn from the hand. The last one can be played.
+    fn available(&self, player: usize) -> PyResult<Vec<String>> {
+        let cards = &self.player(player)?.hand.available_to_play;
+        Ok(self.names(Some(player as u32), cards))
+    }
+    fn blitz_pile(&self, player: usize) -> PyResult<Vec<String>> {
+        let cards = &self.player(player)?.blitz_pile.cards;
+        Ok(self.names(Some(player as u32), cards))
+    }
+    fn post_piles(&self, player: usize) -> PyResult<Vec<Vec<String>>> {
+        let piles = &self.player(player)?.post_pile.piles;
+        Ok(piles
+            .iter()
+            .map(|p| self.names(Some(player as u32), &p.cards))
+            .collect())
+    }
+    fn arena(&self) -> Vec<Vec<String>> {
+        self.0
+            .arena
+            .piles
+            .iter()
+            .map(|p| self.names(None, &p.cards))
+            .collect()
+    }
+}
+impl PyGameState {
+    fn player(&self, player: usize) -> PyResult<&crate::Player> {
+        self.0
+            .players
+            .get(player)
+            .ok_or_else(|| PyValueError::new_err(format!("Player {player} does not exist")))
+    }
+    fn names(&self, owner: Option<u32>, cards: &[u32]) -> Vec<String> {
+        let deck_size = self.0.cards_per_player();
+        cards
+            .iter()
+            .map(|c| card_name(*c, deck_size, owner))
+            .collect()
+    }
+}
+
+#[pymodule]
+fn blitz(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGameStateBuilder>()?;
+    m.add_class::<PyGameState>()?;
+    m.add_class::<PyPlay>()?;
+    m.add_class::<PyScoreboard>()?;
+    m.add_class::<PySnapshot>()?;
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//!Scenarios describe an exact position as JSON, so that games can be started from it instead of from a random deal.
+//! They are used for regression tests, tutorials and puzzles.
+//!
+//! Cards are written as a color letter (`R`, `B`, `G` or `Y`) followed by the number, such as `R5` or `Y10`.
+//! Wild cards are written as `W` followed by which of the owner's wild cards it is, counting from 1.
+//! A card belongs to the player whose piles it is in, unless it is prefixed with its owner's player id, such as `1:B3`.
+//! Cards in the arena must always be prefixed with their owner.
+//!
+//! Every pile is listed from the bottom card to the top card. Cards that are not listed are out of play.
+//! ```json
+//! {
+//!     "drawRate": 1,
+//!     "players": [
+//!         { "hand": ["R7", "G2"], "available": ["B2"], "post": [["Y9", "Y8"], ["G5"]], "blitz": ["R3", "R2"] },
+//!         { "post": [["B9"]], "blitz": ["Y1"] }
+//!     ],
+//!     "arena": [["0:R1"], ["1:B1"]]
+//! }
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{proto, Arena, BlitzPile, Color, GameState, Pile, PlayerHand, PostPile};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Scenario {
+    ///Defaults to 3
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draw_rate: Option<u32>,
+    pub wild_cards_per_player: u32,
+    ///The name of a [`proto::GameMode`], such as `PRACTICE`. Defaults to `CLASSIC`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_mode: Option<String>,
+    pub players: Vec<PlayerScenario>,
+    pub arena: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerScenario {
+    pub hand: Vec<String>,
+    pub available: Vec<String>,
+    pub post: Vec<Vec<String>>,
+    pub blitz: Vec<String>,
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Scenario> {
+        serde_json::from_str(json).with_context(|| "Failed to parse scenario")
+    }
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).with_context(|| "Failed to write scenario")
+    }
+    ///Describes the position of a game in progress.
+    pub fn capture(game: &GameState) -> Scenario {
+        let deck_size = game.cards_per_player();
+        let name = |owner: Option<u32>, card: &u32| card_name(*card, deck_size, owner);
+        let names = |owner, cards: &[u32]| cards.iter().map(|c| name(owner, c)).collect();
+        Scenario {
+            draw_rate: Some(game.draw_rate),
+            wild_cards_per_player: game.wild_cards_per_player,
+            game_mode: Some(game.game_mode.as_str_name().to_string()),
+            players: game
+                .players
+                .iter()
+                .map(|p| {
+                    let owner = Some(p.player_id);
+                    PlayerScenario {
+                        hand: names(owner, &p.hand.in_hand),
+                        available: names(owner, &p.hand.available_to_play),
+                        post: p
+                            .post_pile
+                            .piles
+                            .iter()
+                            .map(|pile| names(owner, &pile.cards))
+                            .collect(),
+                        blitz: names(owner, &p.blitz_pile.cards),
+                    }
+                })
+                .collect(),
+            arena: game
+                .arena
+                .piles
+                .iter()
+                .map(|pile| names(None, &pile.cards))
+                .collect(),
+        }
+    }
+    ///Creates a game in this position. Fails if a card is named twice, sits in another player's piles, or breaks the stacking rules of its pile.
+    pub fn load(&self) -> Result<GameState> {
+        let game_mode = match &self.game_mode {
+            Some(name) => proto::GameMode::from_str_name(name)
+                .ok_or_else(|| anyhow!("gameMode: {name} is not a known game mode"))?,
+            None => proto::GameMode::Classic,
+        };
+        let post_pile_size = self.players.iter().map(|p| p.post.len()).max().unwrap_or(0);
+        let prefs = proto::GamePrefs {
+            draw_rate: self.draw_rate.unwrap_or(3),
+            post_pile_size: post_pile_size.max(1) as u32,
+            score_to_win: 72,
+            blitz_deduction: 10,
+            wild_cards_per_player: self.wild_cards_per_player,
+            game_mode: game_mode as i32,
+            ..Default::default()
+        };
+        let mut game = GameState::new(self.players.len() as u32, prefs)?;
+        let deck_size = game.cards_per_player();
+        let mut seen = vec![false; game.card_context.cards.len()];
+        let mut parse = |path: &str, name: &str, owner: Option<u32>| -> Result<u32> {
+            let card = parse_card(name, deck_size, self.players.len() as u32, owner)
+                .with_context(|| format!("{path}: {name}"))?;
+            if std::mem::replace(&mut seen[card as usize], true) {
+                return Err(anyhow!("{path}: {name} appears more than once"));
+            }
+            Ok(card)
+        };
+
+        for (i, scenario) in self.players.iter().enumerate() {
+            let owner = Some(i as u32);
+            let mut cards = |field: &str, names: &[String]| -> Result<Vec<u32>> {
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(j, name)| parse(&format!("players[{i}].{field}[{j}]"), name, owner))
+                    .collect()
+            };
+            let mut hand = PlayerHand::new(cards("hand", &scenario.hand)?);
+            hand.available_to_play = cards("available", &scenario.available)?;
+            let blitz_pile = BlitzPile::new(cards("blitz", &scenario.blitz)?);
+            let mut post_pile = PostPile::new();
+            for (p, pile_names) in scenario.post.iter().enumerate() {
+                let field = format!("post[{p}]");
+                let mut pile = Pile::from_vec(vec![], Color::Red);
+                for (j, card) in cards(&field, pile_names)?.into_iter().enumerate() {
+                    pile.add_post_card(card, &game.card_context)
+                        .with_context(|| format!("players[{i}].{field}[{j}]"))?;
+                }
+                post_pile.piles.push(pile);
+            }
+            let player = &mut game.players[i];
+            player.hand = hand;
+            player.post_pile = post_pile;
+            player.blitz_pile = blitz_pile;
+        }
+
+        let mut arena = Arena::new();
+        for (p, pile_names) in self.arena.iter().enumerate() {
+            for (j, name) in pile_names.iter().enumerate() {
+                let path = format!("arena[{p}][{j}]");
+                if !name.contains(':') {
+                    return Err(anyhow!("{path}: {name} must be prefixed with its owner"));
+                }
+                let card = parse(&path, name, None)?;
+                let is_first = j == 0;
+                let starts_pile = {
+                    let card = game.card_context.get_card(card as usize)?;
+                    card.number == 1 && !card.is_wild()
+                };
+                if is_first != starts_pile {
+                    return Err(anyhow!(
+                        "{path}: {name} {}",
+                        if is_first {
+                            "cannot start a pile, only a 1 can"
+                        } else {
+                            "can only start a new pile"
+                        }
+                    ));
+                }
+                arena
+                    .verify_add_card(p as u32, card, &game.card_context)
+                    .with_context(|| path.clone())?;
+                arena.add_card(p as u32, card, &game.card_context)?;
+            }
+        }
+        game.arena = arena;
+        Ok(game)
+    }
+}
+
+///Reads a card written as described in the [module documentation](self).
+fn parse_card(name: &str, deck_size: u32, player_count: u32, owner: Option<u32>) -> Result<u32> {
+    let (owner, card) = match name.split_once(':') {
+        Some((prefix, card)) => {
+            let prefix = prefix.parse::<u32>().with_context(|| "Invalid owner")?;
+            if owner.is_some_and(|o| o != prefix) {
+                return Err(anyhow!("Card belongs to another player"));
+            }
+            (prefix, card)
+        }
+        None => (owner.ok_or_else(|| anyhow!("Owner missing"))?, name),
+    };
+    if owner >= player_count {
+        return Err(anyhow!("Owner {owner} does not exist"));
+    }
+    let mut chars = card.chars();
+    let kind = chars.next().ok_or_else(|| anyhow!("Card missing"))?;
+    let number = chars
+        .as_str()
+        .parse::<u32>()
+        .with_context(|| "Invalid card number")?;
+    let offset = match kind.to_ascii_uppercase() {
+        'W' if (1..=deck_size - 40).contains(&number) => 40 + number - 1,
+        'W' => return Err(anyhow!("Wild card {number} does not exist")),
+        _ if !(1..=10).contains(&number) => return Err(anyhow!("Card numbers go from 1 to 10")),
+        'R' => number - 1,
+        'B' => 10 + number - 1,
+        'G' => 20 + number - 1,
+        'Y' => 30 + number - 1,
+        _ => return Err(anyhow!("Unknown color {kind}")),
+    };
+    Ok(owner * deck_size + offset)
+}
+
+///Writes a card as described in the [module documentation](self). The owner is left out when it is `owner`.
+pub(crate) fn card_name(card: u32, deck_size: u32, owner: Option<u32>) -> String {
+    let (card_owner, offset) = (card / deck_size, card % deck_size);
+    let card_name = match offset {
+        0..=39 => format!(
+            "{}{}",
+            ['R', 'B', 'G', 'Y'][(offset / 10) as usize],
+            offset % 10 + 1
+        ),
+        _ => format!("W{}", offset - 40 + 1),
+    };
+    if owner == Some(card_owner) {
+        card_name
+    } else {
+        format!("{card_owner}:{card_name}")
+    }
+}
@@ -6,19 +6,19 @@ use std::sync::Arc;
 use ah::Context;
 use bson::oid::ObjectId;
 use dashmap::DashMap;
-use serde::{Deserialize, Serialize};
 use tokio::task::JoinHandle;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
-use tonic::client;
 use tracing::info;
-const BLITZ_PILE: &str = "BLITZ_PILE";
-const POST_PILE: &str = "POST_PILE";
-const AVAILABLE_PILE: &str = "AVAILABLE_PILE";
+///How often the server advances time-based game rules, such as the draw rate schedule.
+const GAME_CLOCK_PERIOD: std::time::Duration = std::time::Duration::from_millis(250);
 
+use crate::analysis::{GameRecorder, DEFAULT_IDLE_THRESHOLD};
+use crate::daily::{self, DailyResults};
+use crate::prefs::{InvalidPrefs, MAX_PLAYERS};
 use crate::proto;
 use crate::proto::*;
-use crate::server;
+use crate::tables::TableSet;
 use crate::GameState;
 use anyhow as ah;
 
@@ -35,13 +35,17 @@ impl SessionRs {
         SessionRs {
             session_id: session.id.clone(),
             is_joinable: session.is_joinable,
-            is_active: session.game_state.is_some(),
+            is_active: session.is_in_game(),
             players: session.players.clone(),
         }
     }
 }
 type ServerEventChannelTx = flume::Sender<tonic::Result<proto::ServerEvent>>;
 type EventChannelRx = flume::Receiver<tonic::Result<ServerEvent>>;
+type ClientEventChannel = (
+    Option<ServerEventChannelTx>,
+    Option<JoinHandle<core::result::Result<(), anyhow::Error>>>,
+);
 
 ///A session that is either currently waiting to be joined or is already being played
 pub struct Session {
@@ -53,70 +57,111 @@ pub struct Session {
     ///  this game is either already being  or about to be played
     pub is_joinable: bool,
     pub game_state: Option<GameState>,
+    ///The tables of a game for more players than fit around one table. Set instead of `game_state`
+    pub tables: Option<TableSet>,
+    ///Records the plays of the game, to analyze them once it is over
+    pub recorder: Option<GameRecorder>,
+    ///The day of the daily challenge being played, if the game is one
+    pub daily_challenge: Option<u32>,
     pub players: Vec<Player>,
-    pub client_event_channels: Vec<(
-        Option<ServerEventChannelTx>,
-        Option<JoinHandle<core::result::Result<(), anyhow::Error>>>,
-    )>,
+    pub client_event_channels: Vec<ClientEventChannel>,
 }
 impl Session {
     pub fn next_event_id(&self) -> u32 {
         self.server_event_counter.fetch_add(1, Ordering::Relaxed)
     }
+    pub fn is_in_game(&self) -> bool {
+        self.game_state.is_some() || self.tables.is_some()
+    }
+    ///Logs the result of a solo run and, if it was a daily challenge, records it on the leaderboard.
+    pub fn record_solo_result(&self, round_over: &RoundOverEvent, daily_results: &DailyResults) {
+        let Some(solo) = &round_over.solo_result else {
+            return;
+        };
+        info!(
+            session_id = self.id,
+            completed = solo.completed,
+            completion_ms = solo.completion_ms,
+            moves = solo.moves,
+            "Solo run ended"
+        );
+        let Some(day) = self.daily_challenge else {
+            return;
+        };
+        if let Err(e) = daily_results.record(day, &self.players[0].username, solo.clone()) {
+            tracing::error!(
+                session_id = self.id,
+                "Could not record daily challenge run: {e:#}"
+            );
+        }
+    }
+    ///Analyzes the plays of the game once it is over. Only returns the analysis the first time.
+    pub fn take_analysis(&mut self) -> Option<proto::GameAnalysis> {
+        let game = self.game_state.as_ref().filter(|g| g.is_game_over())?;
+        let recorder = self.recorder.take()?;
+        Some(recorder.analyze(game, DEFAULT_IDLE_THRESHOLD))
+    }
+    ///Starts the game with the prefs, named preset or daily challenge in the request. Sessions with more than [`MAX_PLAYERS`] players
+    /// are split into tables. Returns the event that tells each player about the new game, indexed by player id.
     pub fn start_game(
         &mut self,
         rq: StartGameEvent,
-    ) -> anyhow::Result<(Vec<proto::Card>, Vec<proto::PlayerCards>)> {
-        let player = rq.player.unwrap();
+    ) -> anyhow::Result<Vec<ServerRequestStartGameEvent>> {
+        let player = rq.player.context("No player given")?;
         if !player.is_session_admin {
             return Err(ah::anyhow!("Player is not admin"));
         }
         if !self.is_joinable {
             return Err(ah::anyhow!("Session is already in game"));
         }
+        let prefs = match (rq.prefs, rq.preset.as_str()) {
+            (None, "") if rq.daily_challenge => daily::prefs(),
+            (_, _) if rq.daily_challenge => {
+                return Err(InvalidPrefs::new(
+                    "dailyChallenge",
+                    "cannot be given along with prefs or a preset",
+                )
+                .into())
+            }
+            (Some(prefs), "") => prefs,
+            (None, "") => {
+                return Err(InvalidPrefs::new("prefs", "or a preset must be given").into())
+            }
+            (None, name) => crate::presets::get(name)
+                .ok_or_else(|| InvalidPrefs::new("preset", format!("{name} does not exist")))?
+                .prefs(),
+            (Some(_), _) => {
+                return Err(InvalidPrefs::new("preset", "cannot be given along with prefs").into())
+            }
+        };
+        //validation errors are passed on as they are, so that the admin can be told which fields to fix
+        let player_count = self.players.len() as u32;
+        if player_count > MAX_PLAYERS && !rq.daily_challenge {
+            let tables = TableSet::new(player_count, prefs)?;
+            let start_events = tables.start_events();
+            self.tables = Some(tables);
+            self.is_joinable = false;
+            return Ok(start_events);
+        }
+        let game = if rq.daily_challenge {
+            let day = daily::today();
+            self.daily_challenge = Some(day);
+            GameState::new_seeded(player_count, prefs.clone(), daily::seed(day))?
+        } else {
+            GameState::new(player_count, prefs.clone())?
+        };
+        self.recorder = Some(GameRecorder::new(&game));
+        self.game_state = Some(game);
         self.is_joinable = false;
-        self.game_state = Some(
-            GameState::new(self.players.len() as u32, rq.prefs.unwrap())
-                .with_context(|| "Failed to create game state")?,
-        );
-        let global_deck = self
-            .game_state
-            .as_ref()
-            .unwrap()
-            .card_context
-            .cards
-            .iter()
-            .map(|e| proto::Card {
-                player_id: e.player_id,
-                number: e.number,
-                color: e.color as i32,
-                gender: e.gender as i32,
-            })
-            .collect();
-        let player_cards =
-            self.game_state
-                .as_ref()
-                .unwrap()
-                .players
-                .iter()
-                .map(|p| proto::PlayerCards {
-                    hand: Some(PlayerHand {
-                        in_hand: p.hand.in_hand.clone(),
-                        available_to_play: p.hand.available_to_play.clone(),
-                    }),
-                    post: Some(PostPile {
-                        piles: p
-                            .post_pile
-                            .piles
-                            .iter()
-                            .map(|e| proto::Pile {
-                                cards: e.cards.clone(),
-                            })
-                            .collect(),
-                    }),
-                    blitz_pile: p.blitz_pile.cards.clone(),
-                });
-        Ok((global_deck, player_cards.collect()))
+        let global_deck = self.game_state.as_ref().unwrap().global_deck();
+        let player_cards = self.game_state.as_ref().unwrap().player_cards();
+        let start_event = ServerRequestStartGameEvent {
+            prefs: Some(prefs),
+            global_deck: Some(global_deck),
+            player_cards,
+            table_seat: None,
+        };
+        Ok(vec![start_event; player_count as usize])
     }
 
     pub async fn sv_close_channel(&mut self, player_id: u32) -> tonic::Result<()> {
@@ -133,7 +178,7 @@ impl Session {
         let is_admin = player.is_session_admin;
 
         //session admin
-        if self.game_state.is_some() {
+        if self.is_in_game() {
             //send game over event to all players
             let event = Ok(server_event::Event::ServerGameStateAction(
                 ServerGameStateAction::ServerGameOver as i32,
@@ -149,6 +194,7 @@ impl Session {
             info!(session_id, "Game over event sent to all clients");
             //end game
             self.game_state = None;
+            self.tables = None;
             //close all join handles
             for channel in self.client_event_channels.iter_mut() {
                 if let (_, Some(handle)) = channel {
@@ -209,24 +255,97 @@ impl TryFrom<proto::Play> for crate::Play {
                         crate::PlayerAction::TransferToAvailable
                     }
                     PlayerPlayType::ResetHand => crate::PlayerAction::ResetHand,
+                    PlayerPlayType::PostToPost => crate::PlayerAction::PostToPost {
+                        from: p.post_index.context("post_index missing")?,
+                        to: p.to_post_index.context("to_post_index missing")?,
+                    },
                 }),
                 play::Play::CallBlitz(c) => crate::Action::CallBlitz(c.player_index),
             },
         })
     }
 }
+impl From<crate::Play> for proto::Play {
+    fn from(value: crate::Play) -> Self {
+        let arena_play = |play_type: ArenaPlayType, from_index, to_index| {
+            play::Play::ArenaPlay(ArenaPlay {
+                play_type: play_type as i32,
+                from_index,
+                to_index: Some(to_index),
+            })
+        };
+        let player_play = |play_type: PlayerPlayType, post_index, to_post_index| {
+            play::Play::PlayerPlay(PlayerPlay {
+                play_type: play_type as i32,
+                post_index,
+                to_post_index,
+            })
+        };
+        proto::Play {
+            player_id: value.player,
+            play: Some(match value.play {
+                crate::Action::Arena(a) => match a {
+                    crate::ArenaAction::FromAvailableHand(pile) => {
+                        arena_play(ArenaPlayType::FromAvailableHand, None, pile)
+                    }
+                    crate::ArenaAction::FromBlitz(pile) => {
+                        arena_play(ArenaPlayType::FromBlitz, None, pile)
+                    }
+                    crate::ArenaAction::FromPost {
+                        post_pile,
+                        arena_pile,
+                    } => arena_play(ArenaPlayType::FromPost, Some(post_pile), arena_pile),
+                },
+                crate::Action::Player(p) => match p {
+                    crate::PlayerAction::BlitzToPost(post) => {
+                        player_play(PlayerPlayType::BlitzToPost, Some(post), None)
+                    }
+                    crate::PlayerAction::AvailableToPost(post) => {
+                        player_play(PlayerPlayType::AvailableHandToPost, Some(post), None)
+                    }
+                    crate::PlayerAction::TransferToAvailable => {
+                        player_play(PlayerPlayType::TransferToAvailableHand, None, None)
+                    }
+                    crate::PlayerAction::ResetHand => {
+                        player_play(PlayerPlayType::ResetHand, None, None)
+                    }
+                    crate::PlayerAction::PostToPost { from, to } => {
+                        player_play(PlayerPlayType::PostToPost, Some(from), Some(to))
+                    }
+                },
+                crate::Action::CallBlitz(player_index) => {
+                    play::Play::CallBlitz(proto::CallBlitz { player_index })
+                }
+            }),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Server {
     sessions: Arc<DashMap<String, Session>>,
+    daily_results: Arc<DailyResults>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Server {
     pub fn new() -> Self {
         Server {
             sessions: Arc::new(DashMap::new()),
+            daily_results: Arc::new(DailyResults::new()),
         }
     }
+    ///Keeps daily challenge results in `daily_results` instead of only in memory.
+    pub fn with_daily_results(mut self, daily_results: DailyResults) -> Self {
+        self.daily_results = Arc::new(daily_results);
+        self
+    }
+    #[allow(clippy::result_large_err)]
     pub fn create_session(&self, rq: proto::StartSessionRq) -> tonic::Result<proto::Player> {
         let session_id = ObjectId::new().to_hex();
         //make sure the username is not blank
@@ -249,6 +368,9 @@ impl Server {
             id: session_id.clone(),
             is_joinable: true,
             game_state: None,
+            tables: None,
+            recorder: None,
+            daily_challenge: None,
             players: vec![player.clone()],
             client_event_channels: vec![(None, None)],
         };
@@ -256,6 +378,7 @@ impl Server {
         info!(session_id, "Session created");
         Ok(player)
     }
+    #[allow(clippy::result_large_err)]
     pub fn sv_join_session(&self, rq: JoinSessionRq) -> tonic::Result<Player> {
         let session_id = rq.session_id.clone();
         let mut session = self.sessions.get_mut(&session_id).ok_or_else(|| {
@@ -301,11 +424,13 @@ impl Server {
         event_id: u32,
         ack_type: EAcknowledgementType,
         message: String,
+        field_violations: Vec<FieldViolation>,
     ) -> anyhow::Result<()> {
         let ack = Acknowledge {
             event_id,
             acknowledgement_type: ack_type as i32,
             message,
+            field_violations,
         };
         let event = server_event::Event::Acknowledge(ack);
         Self::send_event_to_client(Ok(event), session, player_id, event_id)
@@ -315,6 +440,7 @@ impl Server {
     }
     async fn process_client_events(
         sessions: Arc<DashMap<String, Session>>,
+        daily_results: Arc<DailyResults>,
         session_id: String,
         player_id: u32,
         cancel: tokio::sync::oneshot::Sender<anyhow::Result<()>>,
@@ -344,26 +470,205 @@ impl Server {
                                         tracing::warn!(session_id, "Session does not exist");
                                         continue
                                     };
-                                    let Some(g) = session.game_state.as_mut() else{
+                                    let play: crate::Play = match p.try_into() {
+                                        Ok(play) => play,
+                                        Err(e) => {
+                                            tracing::warn!(
+                                                session_id,
+                                                player_id,
+                                                "Malformed play: {e:#}"
+                                            );
+                                            Self::send_ack_event(
+                                                &session,
+                                                player_id,
+                                                client_event_id,
+                                                EAcknowledgementType::Rejected,
+                                                format!("{e:#}"),
+                                                vec![],
+                                            )
+                                            .await?;
+                                            continue;
+                                        }
+                                    };
+                                    if session.tables.is_some() {
+                                        Self::process_table_play(
+                                            &mut session,
+                                            player_id,
+                                            client_event_id,
+                                            play,
+                                        )
+                                        .await?;
+                                        continue;
+                                    }
+                                    let Session {
+                                        game_state,
+                                        recorder,
+                                        ..
+                                    } = &mut *session;
+                                    let Some(g) = game_state.as_mut() else{
                                         tracing::error!("Game not started. This should not be possible");
                                         continue
                                     };
 
-                                    let event = g.make_play(p.try_into().unwrap());
+                                    if let Some(recorder) = recorder.as_mut() {
+                                        recorder.observe(g);
+                                    }
+                                    let event = g.make_play(play);
+                                    if let (Ok(_), Some(recorder)) = (&event, recorder.as_mut()) {
+                                        recorder.record(g, play, std::time::Instant::now());
+                                    }
                                     match &event {
                                         Err(e) => {
                                             //send an error back to the player that sent this
+                                            Self::reject_play(&mut session, player_id, play, e)
+                                                .await?;
+                                        }
+                                        Ok(e) => {
+                                            Self::broadcast_event(
+                                                Ok(e.clone()),
+                                                &session,
+                                                player_id,
+                                                client_event_id,
+                                                true,
+                                            )
+                                            .await
+                                            .with_context(|| {
+                                                tracing::error!(
+                                                    "Could not send events to all clients"
+                                                );
+                                                "Could not send sevents to all events"
+                                            })?;
+                                            //giving up a solo run ends it straight away
+                                            if let server_event::Event::RoundOver(round_over) = e {
+                                                session
+                                                    .record_solo_result(round_over, &daily_results);
+                                            }
+                                            let round_over = match session.game_state.as_mut() {
+                                                Some(g) if g.should_end_round() => {
+                                                    Some(g.end_round()?)
+                                                }
+                                                _ => None,
+                                            };
+                                            if let Some(round_over) = round_over {
+                                                info!(
+                                                    session_id,
+                                                    round = round_over.round,
+                                                    "Blitz pile emptied. Ending round"
+                                                );
+                                                session.record_solo_result(
+                                                    &round_over,
+                                                    &daily_results,
+                                                );
+                                                Self::broadcast_to_all(
+                                                    server_event::Event::RoundOver(round_over),
+                                                    &session,
+                                                )
+                                                .await
+                                                .with_context(|| {
+                                                    tracing::error!(
+                                                        "Could not send round results to all clients"
+                                                    );
+                                                    "Could not send round results to all clients"
+                                                })?;
+                                            }
+                                        }
+                                    }
+                                }
+                                client_event::Event::Undo(_) => {
+                                    tracing::info!(
+                                        player_id,
+                                        client_event_id,
+                                        "Undo event received"
+                                    );
+                                    let Some(mut session) = sessions.get_mut(&session_id) else {
+                                        tracing::warn!(session_id, "Session does not exist");
+                                        continue;
+                                    };
+                                    let Session {
+                                        game_state,
+                                        recorder,
+                                        ..
+                                    } = &mut *session;
+                                    let Some(g) = game_state.as_mut() else {
+                                        tracing::warn!(
+                                            session_id,
+                                            "Undo received before the game started"
+                                        );
+                                        continue;
+                                    };
+                                    match g.undo(player_id) {
+                                        Ok(e) => {
+                                            if let Some(recorder) = recorder.as_mut() {
+                                                recorder.undo(g, player_id);
+                                            }
+                                            Self::broadcast_event(
+                                                Ok(e),
+                                                &session,
+                                                player_id,
+                                                client_event_id,
+                                                true,
+                                            )
+                                            .await
+                                            .with_context(|| {
+                                                tracing::error!(
+                                                    "Could not send events to all clients"
+                                                );
+                                                "Could not send events to all clients"
+                                            })?;
+                                        }
+                                        Err(e) => {
                                             tracing::warn!(
                                                 session_id,
                                                 player_id,
-                                                "Could not play!"
+                                                "Could not undo: {e}"
                                             );
-                                            let event =
-                                                server_event::Event::GamePlayError(GamePlayError {
-                                                    message: e.to_string(),
-                                                });
+                                            Self::send_ack_event(
+                                                &session,
+                                                player_id,
+                                                client_event_id,
+                                                EAcknowledgementType::Rejected,
+                                                e.to_string(),
+                                                vec![],
+                                            )
+                                            .await?;
+                                        }
+                                    }
+                                }
+                                client_event::Event::Hint(_) => {
+                                    tracing::info!(
+                                        player_id,
+                                        client_event_id,
+                                        "Hint request received"
+                                    );
+                                    let Some(session) = sessions.get(&session_id) else {
+                                        tracing::warn!(session_id, "Session does not exist");
+                                        continue;
+                                    };
+                                    let hints = match session.game_state.as_ref() {
+                                        Some(g) if g.allows_hints() => Ok(g.hint(player_id)),
+                                        Some(_) => {
+                                            Err("Hints are only given in practice and solo games")
+                                        }
+                                        None => Err("Game not started"),
+                                    };
+                                    match hints {
+                                        Ok(hints) => {
+                                            let hints = hints
+                                                .into_iter()
+                                                .map(|h| proto::Hint {
+                                                    play: Some(
+                                                        crate::Play {
+                                                            player: player_id,
+                                                            play: h.play,
+                                                        }
+                                                        .into(),
+                                                    ),
+                                                    score: h.score,
+                                                    reason: h.reason.to_string(),
+                                                })
+                                                .collect();
                                             Self::send_event_to_client(
-                                                Ok(event),
+                                                Ok(server_event::Event::Hints(HintEvent { hints })),
                                                 &session,
                                                 player_id,
                                                 session.next_event_id(),
@@ -377,23 +682,17 @@ impl Server {
                                                 );
                                                 "Could not send event to client"
                                             })?;
-                                            Self::send_ack_event(&session, player_id, session.next_event_id(), EAcknowledgementType::Rejected, format!("{e:?}")).await?;
                                         }
-                                        Ok(e) => {
-                                            Self::broadcast_event(
-                                                Ok(e.clone()),
+                                        Err(e) => {
+                                            Self::send_ack_event(
                                                 &session,
                                                 player_id,
                                                 client_event_id,
-                                                true,
+                                                EAcknowledgementType::Rejected,
+                                                e.to_string(),
+                                                vec![],
                                             )
-                                            .await
-                                            .with_context(|| {
-                                                tracing::error!(
-                                                    "Could not send events to all clients"
-                                                );
-                                                "Could not send sevents to all events"
-                                            })?;
+                                            .await?;
                                         }
                                     }
                                 }
@@ -514,11 +813,15 @@ impl Server {
                                             }
 
                                             ClientGameStateAction::ResetDrawRate => {
-                                                if let Some(g) = session.game_state.as_mut() {
-                                                    g.change_draw_rate(3)
-                                                }
+                                                let new_rate = match session.game_state.as_mut() {
+                                                    Some(g) => {
+                                                        g.reset_draw_rate();
+                                                        g.draw_rate
+                                                    }
+                                                    None => 3,
+                                                };
                                                 proto::server_event::Event::ChangeDrawRate(
-                                                    ChangeDrawRateEvent { new_rate: 3 },
+                                                    ChangeDrawRateEvent { new_rate },
                                                 )
                                             }
                                         };
@@ -574,22 +877,39 @@ impl Server {
                                         continue;
                                     }
                                     let mut session = sessions.get_mut(&session_id).unwrap();
-                                    let (global_deck, player_cards) = session
-                                        .start_game(s.clone())
-                                        .with_context(|| "Failed to start game")?;
+                                    let start_events = match session.start_game(s.clone()) {
+                                        Ok(start_events) => start_events,
+                                        Err(e) => {
+                                            //tell the admin why, and keep the session open so they can try again
+                                            tracing::warn!(session_id, "Could not start game: {e}");
+                                            let field_violations = e
+                                                .downcast_ref::<InvalidPrefs>()
+                                                .map(|e| e.to_proto())
+                                                .unwrap_or_default();
+                                            Self::send_ack_event(
+                                                &session,
+                                                player_id,
+                                                client_event_id,
+                                                EAcknowledgementType::Rejected,
+                                                e.to_string(),
+                                                field_violations,
+                                            )
+                                            .await?;
+                                            continue;
+                                        }
+                                    };
                                     info!(session_id = session_id, "Game started");
 
-                                    tracing::debug!("Player cards {player_cards:?}");
                                     let server_event_id = session.next_event_id();
-                                    for pid in 1..player_cards.len() {
+                                    for (pid, start_event) in
+                                        start_events.iter().enumerate().skip(1)
+                                    {
+                                        tracing::debug!(
+                                            "Player cards {:?}",
+                                            start_event.player_cards
+                                        );
                                         let e = server_event::Event::RequestStartGame(
-                                            ServerRequestStartGameEvent {
-                                                prefs: s.prefs.clone(),
-                                                global_deck: Some(proto::GlobalDeck {
-                                                    cards: global_deck.clone(),
-                                                }),
-                                                player_cards: player_cards.clone(),
-                                            },
+                                            start_event.clone(),
                                         );
                                         Self::send_event_to_client(
                                             Ok(e),
@@ -628,13 +948,7 @@ impl Server {
                                     //Send confirm game started to client
                                     info!("Sending confirm game started to client");
                                     let e = server_event::Event::ConfirmGameStart(
-                                        ServerRequestStartGameEvent {
-                                            prefs: s.prefs.clone(),
-                                            global_deck: Some(proto::GlobalDeck {
-                                                cards: global_deck,
-                                            }),
-                                            player_cards,
-                                        },
+                                        start_events[0].clone(),
                                     );
                                     let session = sessions.get(&session_id).unwrap();
 
@@ -646,6 +960,8 @@ impl Server {
                                     )
                                     .await
                                     .with_context(|| "Failed to send event to client")?;
+                                    drop(session);
+                                    Self::spawn_game_clock(sessions.clone(), session_id.clone());
                                 }
                                 client_event::Event::Acknowledge(a) => {
                                     tracing::info!(
@@ -702,6 +1018,106 @@ impl Server {
             }
         })
     }
+    ///Tells the player why their play was rejected. If the play was a [`crate::Misplay`], the misplay penalty is applied first.
+    pub async fn reject_play(
+        session: &mut Session,
+        player_id: u32,
+        play: crate::Play,
+        error: &anyhow::Error,
+    ) -> anyhow::Result<()> {
+        tracing::warn!(session.id, player_id, "Could not play!");
+        let penalized = match (session.tables.as_mut(), session.game_state.as_mut()) {
+            (Some(tables), _) => tables.penalize_misplay(player_id, play.play, error),
+            (None, Some(g)) => g.penalize_misplay(&play, error),
+            (None, None) => false,
+        };
+        if penalized {
+            info!(session.id, player_id, "Misplay penalty applied");
+        }
+        let event = server_event::Event::GamePlayError(GamePlayError {
+            message: error.to_string(),
+        });
+        Self::send_event_to_client(Ok(event), session, player_id, session.next_event_id())
+            .await
+            .with_context(|| "Could not send event to client")?;
+        Self::send_ack_event(
+            session,
+            player_id,
+            session.next_event_id(),
+            EAcknowledgementType::Rejected,
+            format!("{error:?}"),
+            vec![],
+        )
+        .await
+    }
+    ///Makes a play in a multi-table game. The play's events only go to the players at the table. Once every table has finished the round,
+    /// everyone is sent the combined results and, unless the game is over, the start of the next round at the table they have been reseated at.
+    async fn process_table_play(
+        session: &mut Session,
+        player_id: u32,
+        client_event_id: u32,
+        play: crate::Play,
+    ) -> anyhow::Result<()> {
+        let Some(tables) = session.tables.as_mut() else {
+            return Ok(());
+        };
+        let (table, event) = match tables.make_play(player_id, play.play) {
+            Ok(played) => played,
+            Err(e) => return Self::reject_play(session, player_id, play, &e).await,
+        };
+        let mut events = vec![event];
+        if !matches!(events[0], server_event::Event::RoundOver(_))
+            && tables.tables[table].game.should_end_round()
+        {
+            info!(session.id, table, "Blitz pile emptied at table");
+            events.push(server_event::Event::RoundOver(
+                tables.end_table_round(table)?,
+            ));
+        }
+        let seats = tables.tables[table].seats.clone();
+        let tables_round_over = if tables.all_tables_finished() {
+            Some(tables.rotate()?)
+        } else {
+            None
+        };
+        Self::send_ack_event(
+            session,
+            player_id,
+            client_event_id,
+            EAcknowledgementType::Accepted,
+            String::new(),
+            vec![],
+        )
+        .await?;
+        for event in events {
+            Self::broadcast_to(event, session, &seats)
+                .await
+                .with_context(|| "Could not send events to the table")?;
+        }
+        let Some(round_over) = tables_round_over else {
+            return Ok(());
+        };
+        info!(
+            session.id,
+            round = round_over.round,
+            "Every table has finished the round"
+        );
+        let is_game_over = round_over.is_game_over;
+        Self::broadcast_to_all(server_event::Event::TablesRoundOver(round_over), session)
+            .await
+            .with_context(|| "Could not send round results to all clients")?;
+        if !is_game_over {
+            let start_events = session.tables.as_ref().unwrap().start_events();
+            let server_event_id = session.next_event_id();
+            for (player, start_event) in start_events.into_iter().enumerate() {
+                let e = server_event::Event::RequestStartGame(start_event);
+                Self::send_event_to_client(Ok(e), session, player as u32, server_event_id)
+                    .await
+                    .with_context(|| "Failed to send event to player")?;
+            }
+        }
+        Ok(())
+    }
     pub async fn broadcast_event(
         event: tonic::Result<server_event::Event>,
         session: &Session,
@@ -733,6 +1149,7 @@ impl Server {
                         event_id: server_event_id,
                         acknowledgement_type: EAcknowledgementType::Accepted as i32,
                         message: "".to_string(),
+                        field_violations: vec![],
                     };
                     if let Err(e) = tx.send(Ok(ServerEvent {
                         event_id: server_event_id,
@@ -773,7 +1190,7 @@ impl Server {
                                 session
                                     .in_flight_events
                                     .entry(player.player_game_id)
-                                    .or_insert_with(Vec::new)
+                                    .or_default()
                                     .push(server_event_id);
                             }
                         }
@@ -785,6 +1202,93 @@ impl Server {
         Ok(())
     }
 
+    ///Periodically calls [`GameState::tick`] for the session's game and sends the resulting events to every player.
+    /// Stops once the game has ended or the session is gone. Nothing waits on the task, so it logs its own errors.
+    fn spawn_game_clock(
+        sessions: Arc<DashMap<String, Session>>,
+        session_id: String,
+    ) -> JoinHandle<anyhow::Result<()>> {
+        tokio::spawn(async move {
+            let res = || async {
+                let mut interval = tokio::time::interval(GAME_CLOCK_PERIOD);
+                loop {
+                    interval.tick().await;
+                    let Some(mut session) = sessions.get_mut(&session_id) else {
+                        break;
+                    };
+                    if let Some(tables) = session.tables.as_mut() {
+                        if tables.is_game_over() {
+                            break;
+                        }
+                        let events: Vec<_> = tables
+                            .tick(std::time::Instant::now())?
+                            .into_iter()
+                            .map(|(table, event)| (tables.tables[table].seats.clone(), event))
+                            .collect();
+                        for (seats, event) in events {
+                            info!(session_id, "Game clock event: {event}");
+                            Self::broadcast_to(event, &session, &seats)
+                                .await
+                                .with_context(|| "Could not send game clock event to the table")?;
+                        }
+                        continue;
+                    }
+                    let Some(g) = session.game_state.as_mut() else {
+                        break;
+                    };
+                    if g.is_game_over() {
+                        if let Some(analysis) = session.take_analysis() {
+                            Self::broadcast_to_all(
+                                server_event::Event::Analysis(analysis),
+                                &session,
+                            )
+                            .await
+                            .with_context(|| "Could not send game analysis to all clients")?;
+                        }
+                        break;
+                    }
+                    let events = g.tick(std::time::Instant::now())?;
+                    for event in events {
+                        info!(session_id, "Game clock event: {event}");
+                        Self::broadcast_to_all(event, &session)
+                            .await
+                            .with_context(|| "Could not send game clock event to all clients")?;
+                    }
+                }
+                anyhow::Ok(())
+            };
+            if let Err(e) = res().await {
+                tracing::error!(session_id, "Game clock stopped with an error: {e:#}");
+                return Err(e);
+            }
+            info!(session_id, "Game clock stopped");
+            Ok(())
+        })
+    }
+
+    ///Sends an event that originates from the server (not in response to a client event) to every player in the session.
+    /// Unlike [`Server::broadcast_event`], no acknowledgement is sent to anyone.
+    pub async fn broadcast_to_all(
+        event: server_event::Event,
+        session: &Session,
+    ) -> anyhow::Result<()> {
+        let players: Vec<u32> = session.players.iter().map(|p| p.player_game_id).collect();
+        Self::broadcast_to(event, session, &players).await
+    }
+    ///Like [`Server::broadcast_to_all`], but only to the given players, such as those at one table of a multi-table game.
+    pub async fn broadcast_to(
+        event: server_event::Event,
+        session: &Session,
+        players: &[u32],
+    ) -> anyhow::Result<()> {
+        let server_event_id = session.next_event_id();
+        for player in players {
+            Self::send_event_to_client(Ok(event.clone()), session, *player, server_event_id)
+                .await?;
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(skip(session, event))]
     pub async fn send_event_to_client(
         event: tonic::Result<server_event::Event>,
@@ -803,11 +1307,13 @@ impl Server {
                 "Could not send event to client. Invalid player "
             })?
         {
-            tx.send(event.clone().map(|e| ServerEvent {
+            if let Err(e) = tx.send(event.clone().map(|e| ServerEvent {
                 event_id,
                 event: Some(e),
-            }))
-            .unwrap();
+            })) {
+                tracing::error!("Could not send event. Probably channel closed: {}", e);
+                return Ok(());
+            }
             info!(player_id = player_id, "Sent event to client");
         } else {
             tracing::error!(
@@ -823,7 +1329,7 @@ impl Server {
                     session
                         .in_flight_events
                         .entry(player_id)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(event_id);
                 }
             }
@@ -901,7 +1407,6 @@ impl Server {
         //the first message must be the OpenStream event
         info!("Waiting for first message from client");
         let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<anyhow::Result<()>>();
-        let mut player_option = None;
         match rx.try_next().await {
             Ok(Some(c)) => {
                 let e = c.event.unwrap();
@@ -909,7 +1414,6 @@ impl Server {
                     client_event::Event::OpenStream(e) => {
                         tracing::info!("OpenStream event received");
                         let player = e.player.unwrap();
-                        player_option = Some(player.clone());
 
                         let mut session =
                             self.sessions.get_mut(&player.session_id).ok_or_else(|| {
@@ -932,6 +1436,7 @@ impl Server {
                                 event_id: 0,
                                 acknowledgement_type: EAcknowledgementType::Accepted as i32,
                                 message: "".to_string(),
+                                field_violations: vec![],
                             };
                             let event = proto::server_event::Event::Acknowledge(ack);
 
@@ -955,6 +1460,7 @@ impl Server {
                             //spawn the client event processor and store the join handle
                             let join_handle = Self::process_client_events(
                                 self.sessions.clone(),
+                                self.daily_results.clone(),
                                 player.session_id.clone(),
                                 id,
                                 cancel_tx,
@@ -990,8 +1496,8 @@ impl Server {
 
         cancel_rx
             .await
-            .map_err(|e| tonic::Status::unknown(e.to_string()))?;
-        Ok(())
+            .map_err(|e| tonic::Status::unknown(e.to_string()))?
+            .into_tonic_status()
     }
     ///Ends the session that the player is in. Ends game for all players. Can only be called by the session admin
     pub async fn sv_end_session(&self, player: &proto::Player) -> tonic::Result<()> {
@@ -1119,6 +1625,23 @@ impl proto::session_service_server::SessionService for Server {
         };
         Ok(tonic::Response::new(session))
     }
+    async fn get_rule_presets(
+        &self,
+        _: tonic::Request<()>,
+    ) -> std::result::Result<tonic::Response<RulePresets>, tonic::Status> {
+        let presets = crate::presets::PRESETS
+            .iter()
+            .map(|p| p.to_proto())
+            .collect();
+        Ok(tonic::Response::new(RulePresets { presets }))
+    }
+    async fn get_daily_leaderboard(
+        &self,
+        request: tonic::Request<DailyLeaderboardRq>,
+    ) -> std::result::Result<tonic::Response<DailyLeaderboard>, tonic::Status> {
+        let day = request.into_inner().day.unwrap_or_else(daily::today);
+        Ok(tonic::Response::new(self.daily_results.leaderboard(day)))
+    }
 }
 
 type ResponseStream =
@@ -1138,6 +1661,7 @@ impl proto::game_service_server::GameService for Server {
             stream: stream.into_stream(),
             drop_signal: Some(cancel),
         };
+        #[allow(clippy::result_large_err)]
         let stream = stream.map(move |e| {
             tracing::debug!(
                 client_addr = client_id,
@@ -1169,12 +1693,19 @@ impl From<tonic::Status> for TonicStatus {
         TonicStatus(s)
     }
 }
+impl From<TonicStatus> for tonic::Status {
+    fn from(s: TonicStatus) -> Self {
+        s.0
+    }
+}
 impl From<ah::Error> for TonicStatus {
     fn from(e: ah::Error) -> Self {
         TonicStatus(tonic::Status::new(tonic::Code::Internal, format!("{}", e)))
     }
 }
 pub trait AnyhowIntoTonicStatus<T> {
+    ///tonic::Status is large, but it is what tonic expects every handler to return.
+    #[allow(clippy::result_large_err)]
     fn into_tonic_status(self) -> Result<T, tonic::Status>;
 }
 impl<T> AnyhowIntoTonicStatus<T> for anyhow::Result<T> {
@@ -1198,6 +1729,10 @@ impl Display for server_event::Event {
             server_event::Event::ChangeDrawRate(_) => f.write_str("ChangeDrawRate"),
             server_event::Event::ConfirmGameStart(_) => f.write_str("ConfirmGameStart"),
             server_event::Event::GamePlayError(_) => f.write_str("GamePlayError"),
+            server_event::Event::RoundOver(_) => f.write_str("RoundOver"),
+            server_event::Event::Hints(_) => f.write_str("Hints"),
+            server_event::Event::Analysis(_) => f.write_str("Analysis"),
+            server_event::Event::TablesRoundOver(_) => f.write_str("TablesRoundOver"),
         }
     }
 }
@@ -0,0 +1,67 @@
+//!Searches for the quickest way for a player to empty their blitz pile, while nobody else moves.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::{Action, GameState, Play};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Solution {
+    ///The shortest sequence of moves that empties the blitz pile. Empty if it already is.
+    Solved(Vec<Action>),
+    ///Every reachable position was searched, and none has an empty blitz pile.
+    Unsolvable,
+    ///The search was stopped after visiting the given number of positions.
+    GaveUp,
+}
+
+///Breadth first search over the positions the player can reach with their own moves, assuming the rest of the table stays as it is.
+/// Searches at most `max_positions` distinct positions.
+pub fn solve_blitz_pile(game: &GameState, player: u32, max_positions: usize) -> Solution {
+    let Some(state) = game.players.get(player as usize) else {
+        return Solution::Unsolvable;
+    };
+    if state.blitz_pile.cards.is_empty() {
+        return Solution::Solved(vec![]);
+    }
+    let mut seen = HashSet::from([position_key(game, player)]);
+    let mut queue = VecDeque::from([(game.clone(), vec![])]);
+    while let Some((game, moves)) = queue.pop_front() {
+        for play in game.legal_moves(player) {
+            if matches!(play, Action::CallBlitz(_)) {
+                continue;
+            }
+            let mut next = game.clone();
+            if next.make_play(Play { player, play }).is_err() {
+                continue;
+            }
+            let mut next_moves: Vec<Action> = moves.clone();
+            next_moves.push(play);
+            if next.players[player as usize].blitz_pile.cards.is_empty() {
+                return Solution::Solved(next_moves);
+            }
+            if !seen.insert(position_key(&next, player)) {
+                continue;
+            }
+            if seen.len() >= max_positions {
+                return Solution::GaveUp;
+            }
+            queue.push_back((next, next_moves));
+        }
+    }
+    Solution::Unsolvable
+}
+
+///Everything the player's moves can change: their own piles and the arena.
+fn position_key(game: &GameState, player: u32) -> Vec<Vec<u32>> {
+    let state = &game.players[player as usize];
+    let mut key = vec![
+        state.hand.in_hand.clone(),
+        state.hand.available_to_play.clone(),
+        state.blitz_pile.cards.clone(),
+    ];
+    key.extend(state.post_pile.piles.iter().map(|p| p.cards.clone()));
+    //arena piles are separated from post piles, since either may be empty
+    key.push(vec![]);
+    key.extend(game.arena.piles.iter().map(|p| p.cards.clone()));
+    key
+}
@@ -0,0 +1,256 @@
+//!Games for more players than fit around one table. Players are split into several tables, each playing its own [`GameState`],
+//! and are reseated by their combined standings once every table has finished a round.
+
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+
+use crate::prefs::{InvalidPrefs, MAX_PLAYERS};
+use crate::{proto, Action, GameState, Play, RoundScore, Scoreboard};
+
+///One table of a [`TableSet`].
+pub struct Table {
+    ///The session player id sitting in each seat. The seat index is the player id within `game`.
+    pub seats: Vec<u32>,
+    pub game: GameState,
+}
+impl Table {
+    ///Whether the table has scored its round and is waiting for the other tables.
+    pub fn is_finished(&self) -> bool {
+        self.game.scoreboard.scores.iter().any(|s| !s.is_empty())
+    }
+}
+
+pub struct TableSet {
+    pub tables: Vec<Table>,
+    ///Combined scores of every player, indexed by session player id.
+    pub scoreboard: Scoreboard,
+    ///The number of rounds every table has finished.
+    pub round: u32,
+    prefs: proto::GamePrefs,
+    is_game_over: bool,
+}
+impl TableSet {
+    ///Seats `player_count` players at as few tables as possible, with table sizes differing by at most one.
+    /// Only classic games that end by [`proto::WinCondition::ScoreToWin`] or [`proto::WinCondition::FixedRounds`] can be split into tables.
+    pub fn new(player_count: u32, prefs: proto::GamePrefs) -> Result<TableSet> {
+        if prefs.game_mode != proto::GameMode::Classic as i32 {
+            return Err(
+                InvalidPrefs::new("gameMode", "must be CLASSIC for multi-table games").into(),
+            );
+        }
+        let win_condition = proto::WinCondition::try_from(prefs.win_condition);
+        if !matches!(
+            win_condition,
+            Ok(proto::WinCondition::ScoreToWin | proto::WinCondition::FixedRounds)
+        ) {
+            return Err(InvalidPrefs::new(
+                "winCondition",
+                "must be SCORE_TO_WIN or FIXED_ROUNDS for multi-table games",
+            )
+            .into());
+        }
+        //every table must be a valid game on its own, and the smallest table is seated last
+        let smallest_table = Self::table_sizes(player_count).last().copied().unwrap_or(0);
+        crate::prefs::validate(smallest_table, &prefs)?;
+        let mut table_set = TableSet {
+            tables: vec![],
+            scoreboard: Scoreboard::new(player_count),
+            round: 0,
+            prefs,
+            is_game_over: false,
+        };
+        table_set.seat_players(&(0..player_count).collect::<Vec<_>>())?;
+        Ok(table_set)
+    }
+    ///The size of each table for `player_count` players.
+    pub fn table_sizes(player_count: u32) -> Vec<u32> {
+        let table_count = player_count.div_ceil(MAX_PLAYERS).max(1);
+        (0..table_count)
+            .map(|i| player_count / table_count + u32::from(i < player_count % table_count))
+            .collect()
+    }
+    ///Deals a new game at every table, filling the tables in order with `players`.
+    fn seat_players(&mut self, players: &[u32]) -> Result<()> {
+        //tables never end the game on their own. The table set decides once every table has finished the round
+        let table_prefs = proto::GamePrefs {
+            win_condition: proto::WinCondition::FixedRounds as i32,
+            round_limit: u32::MAX,
+            ..self.prefs.clone()
+        };
+        let mut players = players.iter().copied();
+        self.tables = Self::table_sizes(self.scoreboard.scores.len() as u32)
+            .into_iter()
+            .map(|size| {
+                Ok(Table {
+                    seats: players.by_ref().take(size as usize).collect(),
+                    game: GameState::new(size, table_prefs.clone())?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+    ///The table and seat of a session player.
+    pub fn seat_of(&self, player: u32) -> Option<(usize, u32)> {
+        self.tables.iter().enumerate().find_map(|(t, table)| {
+            let seat = table.seats.iter().position(|p| *p == player)?;
+            Some((t, seat as u32))
+        })
+    }
+    ///The events that start the game for each session player: the prefs, and the deck and cards of the player's table.
+    pub fn start_events(&self) -> Vec<proto::ServerRequestStartGameEvent> {
+        let mut events = vec![Default::default(); self.scoreboard.scores.len()];
+        for (t, table) in self.tables.iter().enumerate() {
+            let global_deck = table.game.global_deck();
+            let player_cards = table.game.player_cards();
+            for (seat, player) in table.seats.iter().enumerate() {
+                events[*player as usize] = proto::ServerRequestStartGameEvent {
+                    prefs: Some(self.prefs.clone()),
+                    global_deck: Some(global_deck.clone()),
+                    player_cards: player_cards.clone(),
+                    table_seat: Some(proto::TableSeat {
+                        table: t as u32,
+                        seat: seat as u32,
+                        players: table.seats.clone(),
+                    }),
+                };
+            }
+        }
+        events
+    }
+    ///Makes a play at the player's table. Returns the table index along with the event, which should only be sent to that table.
+    /// Calling blitz ends the round at the table, and is only allowed once the caller's blitz pile is empty.
+    pub fn make_play(
+        &mut self,
+        player: u32,
+        play: Action,
+    ) -> Result<(usize, proto::server_event::Event)> {
+        if self.is_game_over {
+            return Err(anyhow!("Game is over"));
+        }
+        let (t, seat) = self
+            .seat_of(player)
+            .ok_or_else(|| anyhow!("Player is not seated at any table"))?;
+        let table = &mut self.tables[t];
+        if table.is_finished() {
+            return Err(anyhow!(
+                "Table is waiting for the other tables to finish the round"
+            ));
+        }
+        if let Action::CallBlitz(_) = play {
+            if !table.game.players[seat as usize].can_call_blitz() {
+                return Err(anyhow!(
+                    "Blitz can only be called once the blitz pile is empty"
+                ));
+            }
+            let round_over = self.end_table_round(t)?;
+            return Ok((t, proto::server_event::Event::RoundOver(round_over)));
+        }
+        let event = table.game.make_play(Play { player: seat, play })?;
+        Ok((t, event))
+    }
+    ///Applies the misplay penalty at the player's table. Returns whether a penalty was applied.
+    pub fn penalize_misplay(&mut self, player: u32, play: Action, error: &anyhow::Error) -> bool {
+        let Some((t, seat)) = self.seat_of(player) else {
+            return false;
+        };
+        self.tables[t]
+            .game
+            .penalize_misplay(&Play { player: seat, play }, error)
+    }
+    ///Calls [`GameState::tick`] at every table still playing the round. Returns each event along with the index of the table it should be sent to.
+    pub fn tick(&mut self, now: Instant) -> Result<Vec<(usize, proto::server_event::Event)>> {
+        let mut events = vec![];
+        for (t, table) in self.tables.iter_mut().enumerate() {
+            if !table.is_finished() {
+                events.extend(table.game.tick(now)?.into_iter().map(|e| (t, e)));
+            }
+        }
+        Ok(events)
+    }
+    ///Ends the round at a table. The event carries no cards, since the players are dealt again once every table has finished.
+    pub fn end_table_round(&mut self, table: usize) -> Result<proto::RoundOverEvent> {
+        let table = self
+            .tables
+            .get_mut(table)
+            .ok_or_else(|| anyhow!("Table index out of bounds"))?;
+        if table.is_finished() {
+            return Err(anyhow!("Table has already finished the round"));
+        }
+        let mut round_over = table.game.end_round()?;
+        round_over.player_cards.clear();
+        Ok(round_over)
+    }
+    pub fn all_tables_finished(&self) -> bool {
+        self.tables.iter().all(|t| t.is_finished())
+    }
+    ///Once every table has finished the round, adds their scores to the combined scoreboard and, unless that ends the game,
+    /// reseats the players by their standings: the leaders at the first table, and so on. Returns the event describing the results.
+    pub fn rotate(&mut self) -> Result<proto::TablesRoundOverEvent> {
+        if !self.all_tables_finished() {
+            return Err(anyhow!("Not every table has finished the round"));
+        }
+        let mut round_scores = vec![RoundScore::default(); self.scoreboard.scores.len()];
+        let mut tables = vec![];
+        for table in self.tables.iter() {
+            let table_totals = table.game.scoreboard.get_totals();
+            let mut scores = vec![];
+            for (seat, player) in table.seats.iter().enumerate() {
+                let score = table.game.scoreboard.breakdowns[seat]
+                    .first()
+                    .copied()
+                    .unwrap_or_default();
+                round_scores[*player as usize] = score;
+                scores.push(score.to_proto(seat as u32, table_totals[seat]));
+            }
+            tables.push(proto::TableRoundScores {
+                players: table.seats.clone(),
+                scores,
+            });
+        }
+        let round = self.round;
+        self.scoreboard.add_round(round, round_scores.clone());
+        self.round += 1;
+        self.is_game_over = match proto::WinCondition::try_from(self.prefs.win_condition) {
+            Ok(proto::WinCondition::FixedRounds) => self.round >= self.prefs.round_limit,
+            _ => self
+                .scoreboard
+                .get_totals()
+                .iter()
+                .any(|s| *s >= self.prefs.score_to_win as i32),
+        };
+        if !self.is_game_over {
+            self.seat_players(&self.standings())?;
+        }
+        let totals = self.scoreboard.get_totals();
+        Ok(proto::TablesRoundOverEvent {
+            round,
+            tables,
+            scores: round_scores
+                .iter()
+                .zip(totals)
+                .enumerate()
+                .map(|(player, (score, total))| score.to_proto(player as u32, total))
+                .collect(),
+            standings: self.standings(),
+            is_game_over: self.is_game_over,
+            winner: self.winner(),
+        })
+    }
+    ///Session player ids ordered from first to last place by combined score, with ties broken by [`Scoreboard::tie_break_order`].
+    pub fn standings(&self) -> Vec<u32> {
+        let totals = self.scoreboard.get_totals();
+        let mut standings = self.scoreboard.tie_break_order();
+        standings.sort_by_key(|p| std::cmp::Reverse(totals[*p as usize]));
+        standings
+    }
+    pub fn winner(&self) -> Option<u32> {
+        if !self.is_game_over {
+            return None;
+        }
+        self.standings().first().copied()
+    }
+    pub fn is_game_over(&self) -> bool {
+        self.is_game_over
+    }
+}
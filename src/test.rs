@@ -2,14 +2,14 @@
 mod tests {
     use std::io::Write;
 
-    use super::*;
+    use crate::*;
 
     #[test]
     fn gen_csv_combos() {
         let header =
             "number_bottom,number_top,gender_bottom,gender_top,#is_red,#is_blue,#is_green,#is_yellow";
 
-        let colors = vec!["red", "blue", "green", "yellow"];
+        let colors = ["red", "blue", "green", "yellow"];
         //generate numbers 1-10 for each color
         let combos = colors
             .iter()
@@ -34,4 +34,1341 @@ mod tests {
         let combos = combos.join("\n");
         write!(file, "{}", combos).unwrap();
     }
+
+    #[test]
+    fn auto_end_round_scores_and_deals() {
+        let mut game = GameStateBuilder::new()
+            .with_auto_end_round(true)
+            .build()
+            .unwrap();
+        assert!(!game.should_end_round());
+        game.players[0].blitz_pile.clear();
+        assert!(game.should_end_round());
+
+        let round_over = game.end_round().unwrap();
+        assert_eq!(round_over.round, 0);
+        assert_eq!(round_over.scores[0].round_score, 0);
+        assert_eq!(round_over.scores[1].round_score, -20);
+        assert!(!round_over.is_game_over);
+        assert_eq!(round_over.player_cards.len(), 2);
+        assert_eq!(game.round, 1);
+        assert!(!game.should_end_round());
+    }
+
+    #[test]
+    fn post_to_post_follows_stacking_rules() {
+        let mut game = GameStateBuilder::new().build().unwrap();
+        //red 5, red 4 and blue 4 from player 0's deck
+        game.players[0].post_pile = PostPile::from_vec(vec![
+            Pile::from_vec(vec![4], Color::Red),
+            Pile::from_vec(vec![3], Color::Red),
+            Pile::from_vec(vec![13], Color::Blue),
+        ]);
+        let post_to_post = |from, to| Play {
+            player: 0,
+            play: Action::Player(PlayerAction::PostToPost { from, to }),
+        };
+        assert!(game.make_play(post_to_post(2, 0)).is_err());
+        assert!(game.make_play(post_to_post(1, 1)).is_err());
+        game.make_play(post_to_post(1, 0)).unwrap();
+        assert_eq!(game.players[0].post_pile.piles[0].cards, vec![4, 3]);
+        assert!(game.players[0].post_pile.piles[1].cards.is_empty());
+
+        //a post to post play from a client must name both piles
+        let play = |post_index, to_post_index| proto::Play {
+            player_id: 0,
+            play: Some(proto::play::Play::PlayerPlay(proto::PlayerPlay {
+                play_type: proto::PlayerPlayType::PostToPost as i32,
+                post_index,
+                to_post_index,
+            })),
+        };
+        assert!(Play::try_from(play(Some(0), None)).is_err());
+        assert!(Play::try_from(play(None, Some(1))).is_err());
+        let converted = Play::try_from(play(Some(2), Some(1))).unwrap();
+        assert_eq!(converted.play, post_to_post(2, 1).play);
+    }
+
+    #[test]
+    fn empty_post_piles_are_refilled_from_the_blitz_pile() {
+        //red 1, red 5 and blue 4 on the post piles, then yellow 2 on top of green 7 in the blitz pile
+        let deal = |rule| {
+            let mut game = GameStateBuilder::new()
+                .with_post_refill_rule(rule)
+                .build()
+                .unwrap();
+            game.players[0].post_pile = PostPile::from_vec(vec![
+                Pile::from_vec(vec![0], Color::Red),
+                Pile::from_vec(vec![4], Color::Red),
+                Pile::from_vec(vec![13], Color::Blue),
+            ]);
+            game.players[0].blitz_pile = BlitzPile::new(vec![26, 31]);
+            game
+        };
+        let play = |play| Play { player: 0, play };
+        let red_1_to_arena = play(Action::Arena(ArenaAction::FromPost {
+            post_pile: 0,
+            arena_pile: 0,
+        }));
+
+        let mut game = deal(proto::PostRefillRule::AutomaticRefill);
+        let event = game.make_play(red_1_to_arena).unwrap();
+        assert_eq!(game.players[0].post_pile.piles[0].cards, vec![31]);
+        assert_eq!(game.players[0].blitz_pile.cards, vec![26]);
+        let proto::server_event::Event::GameStateChange(change) = event else {
+            panic!("expected a game state change, got {event:?}");
+        };
+        let refilled = &change.player_state_changes[0];
+        assert_eq!(refilled.new_blitz_pile.as_ref().unwrap().cards, vec![26]);
+        assert!(refilled.new_post_pile.is_some());
+
+        //manual refills leave the pile empty until the player fills it
+        let mut game = deal(proto::PostRefillRule::ManualRefill);
+        game.make_play(red_1_to_arena).unwrap();
+        assert!(game.players[0].post_pile.piles[0].cards.is_empty());
+        game.make_play(play(Action::Player(PlayerAction::TransferToAvailable)))
+            .unwrap();
+
+        //forced refills allow nothing but moving a blitz card onto the empty pile
+        let mut game = deal(proto::PostRefillRule::ForcedRefill);
+        game.make_play(red_1_to_arena).unwrap();
+        let transfer = play(Action::Player(PlayerAction::TransferToAvailable));
+        let err = game.make_play(transfer).unwrap_err();
+        assert!(err.to_string().contains("refilled"), "{err}");
+        assert!(game
+            .make_play(play(Action::Player(PlayerAction::BlitzToPost(1))))
+            .is_err());
+        game.make_play(play(Action::Player(PlayerAction::BlitzToPost(0))))
+            .unwrap();
+        assert_eq!(game.players[0].post_pile.piles[0].cards, vec![31]);
+        game.make_play(transfer).unwrap();
+    }
+
+    #[test]
+    fn misplays_are_penalized_and_lock_the_player_out() {
+        let mut game = GameStateBuilder::new()
+            .with_misplay_penalty(3)
+            .with_misplay_lockout(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        //a red 5 cannot start an arena pile
+        game.players[0].blitz_pile = BlitzPile::new(vec![4]);
+        let unpenalized = game.clone();
+        let misplay = Play {
+            player: 0,
+            play: Action::Arena(ArenaAction::FromBlitz(0)),
+        };
+        let transfer = |player| Play {
+            player,
+            play: Action::Player(PlayerAction::TransferToAvailable),
+        };
+        let err = game.make_play(misplay).unwrap_err();
+        assert!(err.is::<Misplay>(), "{err}");
+        assert!(!game.penalize_misplay(&misplay, &anyhow::anyhow!("Game is over")));
+        assert!(game.penalize_misplay(&misplay, &err));
+        assert!(game.is_locked_out(0));
+        assert!(!game.is_locked_out(1));
+
+        //every play is rejected during the lock-out, and further misplays are not penalized again
+        let err = game.make_play(transfer(0)).unwrap_err();
+        assert!(err.to_string().contains("locked out"), "{err}");
+        let err = game.make_play(misplay).unwrap_err();
+        assert!(!err.is::<Misplay>(), "{err}");
+        assert!(!game.penalize_misplay(&misplay, &err));
+        game.make_play(transfer(1)).unwrap();
+
+        //the lock-out ends once its time has passed
+        game.locked_until[0] = Some(std::time::Instant::now());
+        assert!(!game.is_locked_out(0));
+        game.make_play(transfer(0)).unwrap();
+
+        let penalized = game.end_round().unwrap().scores[0].round_score;
+        let mut unpenalized = unpenalized;
+        assert_eq!(
+            penalized,
+            unpenalized.end_round().unwrap().scores[0].round_score - 3
+        );
+    }
+
+    #[test]
+    fn game_clock_applies_the_draw_rate_schedule() {
+        use std::time::Duration;
+        let step = |from_round, after_seconds, draw_rate| proto::DrawRateStep {
+            from_round,
+            after_seconds,
+            draw_rate,
+        };
+        let mut game = GameStateBuilder::new()
+            .with_draw_rate_schedule(vec![
+                step(0, Some(30), 1),
+                step(1, None, 2),
+                step(1, Some(10), 1),
+            ])
+            .build()
+            .unwrap();
+        let changed_to = |events: Vec<proto::server_event::Event>| match events.as_slice() {
+            [] => None,
+            [proto::server_event::Event::ChangeDrawRate(e)] => Some(e.new_rate),
+            _ => panic!("unexpected events {events:?}"),
+        };
+        let started = game.round_started;
+        assert_eq!(changed_to(game.tick(started).unwrap()), None);
+        assert_eq!(game.draw_rate, 3);
+        assert_eq!(
+            changed_to(game.tick(started + Duration::from_secs(31)).unwrap()),
+            Some(1)
+        );
+        assert_eq!(game.draw_rate, 1);
+        //a step is only announced once
+        assert_eq!(
+            changed_to(game.tick(started + Duration::from_secs(40)).unwrap()),
+            None
+        );
+
+        //no step applies this early in the round, so the starting draw rate comes back
+        game.change_draw_rate(2);
+        game.reset_draw_rate();
+        assert_eq!(game.draw_rate, 3);
+
+        //a later round has its own steps, the most specific one winning
+        game.end_round().unwrap();
+        let started = game.round_started;
+        assert_eq!(changed_to(game.tick(started).unwrap()), Some(2));
+        assert_eq!(
+            changed_to(game.tick(started + Duration::from_secs(11)).unwrap()),
+            Some(1)
+        );
+        game.change_draw_rate(3);
+        game.reset_draw_rate();
+        assert_eq!(game.draw_rate, 2);
+
+        //a step that does not apply from the start of the next round stops applying when it begins
+        let mut game = GameStateBuilder::new()
+            .with_draw_rate_schedule(vec![step(0, Some(30), 1)])
+            .build()
+            .unwrap();
+        let started = game.round_started;
+        assert_eq!(
+            changed_to(game.tick(started + Duration::from_secs(31)).unwrap()),
+            Some(1)
+        );
+        game.end_round().unwrap();
+        let started = game.round_started;
+        assert_eq!(changed_to(game.tick(started).unwrap()), Some(3));
+        assert_eq!(game.draw_rate, 3);
+        assert_eq!(
+            changed_to(game.tick(started + Duration::from_secs(31)).unwrap()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn game_clock_ends_timed_games() {
+        use std::time::Duration;
+        let mut game = GameStateBuilder::new()
+            .with_win_condition(proto::WinCondition::TimeLimit)
+            .with_time_limit(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let started = game.game_started;
+        assert!(game
+            .tick(started + Duration::from_secs(59))
+            .unwrap()
+            .is_empty());
+        let events = game.tick(started + Duration::from_secs(60)).unwrap();
+        let [proto::server_event::Event::RoundOver(round_over)] = events.as_slice() else {
+            panic!("unexpected events {events:?}");
+        };
+        assert!(round_over.is_game_over);
+        assert!(game.is_game_over());
+        assert!(game
+            .tick(started + Duration::from_secs(61))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn round_scores_break_down_by_rule() {
+        //player 0 completed a red pile and emptied their blitz pile, player 1 played a blue 1 and 2 and kept three blitz cards
+        let end_of_round = |builder: GameStateBuilder| {
+            let mut game = builder.build().unwrap();
+            let card = |player_id, color, number| {
+                let cards = &game.card_context.cards;
+                cards
+                    .iter()
+                    .position(|c| {
+                        c.player_id == player_id && c.color == color && c.number == number
+                    })
+                    .unwrap() as u32
+            };
+            let reds = (1..=10).map(|n| card(0, Color::Red, n)).collect();
+            let blues = vec![card(1, Color::Blue, 1), card(1, Color::Blue, 2)];
+            game.arena.piles = vec![
+                Pile::from_vec(reds, Color::Red),
+                Pile::from_vec(blues, Color::Blue),
+            ];
+            game.players[0].blitz_pile.clear();
+            game.players[1].blitz_pile.cards.truncate(3);
+            game.score_round();
+            game.scoreboard
+                .breakdowns
+                .iter()
+                .map(|b| b[0])
+                .collect::<Vec<_>>()
+        };
+
+        let defaults = end_of_round(GameStateBuilder::new());
+        assert_eq!(
+            defaults,
+            [
+                RoundScore {
+                    arena_cards: 10,
+                    arena_points: 10,
+                    ..Default::default()
+                },
+                RoundScore {
+                    arena_cards: 2,
+                    blitz_cards_left: 3,
+                    arena_points: 2,
+                    blitz_points: -6,
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let custom = end_of_round(
+            GameStateBuilder::new()
+                .with_arena_card_points(2)
+                .with_blitz_card_points(-3)
+                .with_completed_pile_bonus(5)
+                .with_blitz_bonus(4),
+        );
+        assert_eq!(
+            custom,
+            [
+                RoundScore {
+                    arena_cards: 10,
+                    arena_points: 20,
+                    completed_pile_points: 5,
+                    blitz_bonus: 4,
+                    ..Default::default()
+                },
+                RoundScore {
+                    arena_cards: 2,
+                    blitz_cards_left: 3,
+                    arena_points: 4,
+                    blitz_points: -9,
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(custom[0].total(), 29);
+        assert_eq!(custom[1].total(), -5);
+    }
+
+    #[test]
+    fn win_conditions_decide_the_end_and_the_standings() {
+        let now = std::time::Instant::now();
+        //each player's (arena points, blitz points) for a round
+        let add_round = |game: &mut GameState, points: &[(i32, i32)]| {
+            let scores = points
+                .iter()
+                .map(|(arena_points, blitz_points)| RoundScore {
+                    arena_points: *arena_points,
+                    blitz_points: *blitz_points,
+                    ..Default::default()
+                })
+                .collect();
+            game.scoreboard.add_round(game.round, scores);
+        };
+
+        let mut game = GameStateBuilder::new()
+            .with_player_count(3)
+            .with_score_to_win(10)
+            .build()
+            .unwrap();
+        add_round(&mut game, &[(10, -2), (8, 0), (3, 0)]);
+        assert!(!game.reached_win_condition(now));
+        add_round(&mut game, &[(2, 0), (2, 0), (0, 0)]);
+        assert!(game.reached_win_condition(now));
+        //players 0 and 1 are tied on 10, and player 1 lost fewer points to their blitz pile
+        assert_eq!(game.standings(), [1, 0, 2]);
+
+        let mut game = GameStateBuilder::new()
+            .with_win_condition(proto::WinCondition::FixedRounds)
+            .with_round_limit(2)
+            .build()
+            .unwrap();
+        add_round(&mut game, &[(0, 0), (5, 0)]);
+        assert!(!game.reached_win_condition(now));
+        game.round = 1;
+        add_round(&mut game, &[(5, 0), (0, 0)]);
+        assert!(game.reached_win_condition(now));
+        //tied on every count, so the lower id goes first
+        assert_eq!(game.standings(), [0, 1]);
+
+        let mut game = GameStateBuilder::new()
+            .with_win_condition(proto::WinCondition::RoundWins)
+            .with_round_wins_to_win(2)
+            .build()
+            .unwrap();
+        add_round(&mut game, &[(0, 0), (40, 0)]);
+        //a tied round goes to the player who lost fewer points to their blitz pile
+        add_round(&mut game, &[(10, 0), (12, -2)]);
+        assert_eq!(game.scoreboard.round_wins(), [1, 1]);
+        assert!(!game.reached_win_condition(now));
+        add_round(&mut game, &[(1, 0), (0, 0)]);
+        assert!(game.reached_win_condition(now));
+        //round wins count before the far higher total of player 1
+        assert_eq!(game.scoreboard.round_wins(), [2, 1]);
+        assert_eq!(game.scoreboard.get_totals(), [11, 50]);
+        assert_eq!(game.standings(), [0, 1]);
+    }
+
+    #[test]
+    fn wild_cards_stand_in_for_the_next_card() {
+        let mut game = GameStateBuilder::new()
+            .with_wild_cards_per_player(2)
+            .with_wild_card_bonus(5)
+            .build()
+            .unwrap();
+        let play = |play| Play { player: 0, play };
+        //player 0's deck is red 1-10 at 0-9, ..., then wild cards at 40 and 41
+        game.arena.piles.push(Pile::from_vec(vec![0], Color::Red));
+        game.players[0].hand.available_to_play = vec![2, 40];
+        game.make_play(play(Action::Arena(ArenaAction::FromAvailableHand(0))))
+            .unwrap();
+        game.make_play(play(Action::Arena(ArenaAction::FromAvailableHand(0))))
+            .unwrap();
+        assert_eq!(game.arena.piles[0].cards, vec![0, 40, 2]);
+
+        //a wild card on a red 5 counts as a red 4, so only a red 3 may follow
+        game.players[0].post_pile = PostPile::from_vec(vec![Pile::from_vec(vec![4], Color::Red)]);
+        game.players[0].blitz_pile = BlitzPile::new(vec![1, 3, 41]);
+        game.make_play(play(Action::Player(PlayerAction::BlitzToPost(0))))
+            .unwrap();
+        assert!(game
+            .make_play(play(Action::Player(PlayerAction::BlitzToPost(0))))
+            .is_err());
+
+        game.players[0].blitz_pile.clear();
+        game.score_round();
+        assert_eq!(game.scoreboard.scores[0], vec![3 + 5]);
+    }
+
+    #[test]
+    fn invalid_prefs_name_every_bad_field() {
+        let err = GameStateBuilder::new()
+            .with_draw_rate(0)
+            .with_post_pile_size(31)
+            .with_score_to_win(0)
+            .build()
+            .err()
+            .unwrap();
+        let err = err.downcast::<prefs::InvalidPrefs>().unwrap();
+        let fields: Vec<_> = err.violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["postPileSize", "drawRate", "scoreToWin"]);
+        assert!(GameStateBuilder::new().build().is_ok());
+
+        //counts large enough to overflow are reported, not panicked on
+        let cooperative = |wild_cards_per_player| proto::GamePrefs {
+            draw_rate: 3,
+            post_pile_size: 3,
+            wild_cards_per_player,
+            time_limit_seconds: 60,
+            cooperative_arena_goal: u32::MAX,
+            game_mode: proto::GameMode::Cooperative as i32,
+            ..Default::default()
+        };
+        for (player_count, wild_cards) in [(2, u32::MAX), (8, 600_000_000), (u32::MAX, 1)] {
+            let err = prefs::validate(player_count, &cooperative(wild_cards)).unwrap_err();
+            assert!(err
+                .violations
+                .iter()
+                .any(|v| v.field == "cooperativeArenaGoal"));
+        }
+    }
+
+    #[test]
+    fn presets_are_valid_and_picked_by_name() {
+        for preset in presets::PRESETS {
+            for player_count in 2..=prefs::MAX_PLAYERS {
+                let result = prefs::validate(player_count, &preset.prefs());
+                assert!(result.is_ok(), "{} is invalid: {result:?}", preset.name);
+            }
+        }
+        assert_eq!(presets::get("Quick").unwrap().name, "quick");
+        assert!(presets::get("speedy").is_none());
+
+        let start = |prefs, preset: &str| proto::StartGameEvent {
+            player: Some(session(2).players[0].clone()),
+            prefs,
+            preset: preset.to_string(),
+            ..Default::default()
+        };
+        let violated_field = |rq| {
+            let err = session(2).start_game(rq).err().unwrap();
+            err.downcast::<prefs::InvalidPrefs>().unwrap().violations[0]
+                .field
+                .clone()
+        };
+        assert_eq!(violated_field(start(None, "speedy")), "preset");
+        let prefs = Some(presets::get("quick").unwrap().prefs());
+        assert_eq!(violated_field(start(prefs.clone(), "quick")), "preset");
+        assert_eq!(violated_field(start(None, "")), "prefs");
+
+        let mut session = session(2);
+        let started = session.start_game(start(None, "QUICK")).unwrap();
+        assert_eq!(started[1].prefs, prefs);
+        assert_eq!(session.game_state.unwrap().score_to_win, 30);
+    }
+
+    #[test]
+    fn elimination_removes_the_lowest_total_each_round() {
+        let mut game = GameStateBuilder::new()
+            .with_player_count(3)
+            .with_game_mode(proto::GameMode::Elimination)
+            .build()
+            .unwrap();
+        //player 1 has the fewest blitz cards left, player 2 the most
+        game.players[1].blitz_pile.cards.truncate(5);
+        game.players[2].blitz_pile.cards.truncate(9);
+        let round = game.end_round().unwrap();
+        assert_eq!(round.eliminated, Some(0));
+        assert!(!round.is_game_over);
+        assert!(game.players[0].is_eliminated());
+        assert!(game.players[0].hand.in_hand.is_empty());
+        assert!(!game.players[1].hand.in_hand.is_empty());
+        assert!(game
+            .make_play(Play {
+                player: 0,
+                play: Action::Player(PlayerAction::ResetHand),
+            })
+            .is_err());
+
+        game.players[2].blitz_pile.cards.truncate(1);
+        let round = game.end_round().unwrap();
+        assert_eq!(round.eliminated, Some(1));
+        assert!(round.is_game_over);
+        assert_eq!(round.winner, Some(2));
+        assert_eq!(round.standings, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn cooperative_game_is_won_or_lost_by_the_team() {
+        let cooperative = || {
+            GameStateBuilder::new()
+                .with_game_mode(proto::GameMode::Cooperative)
+                .with_cooperative_arena_goal(2)
+                .with_time_limit(Duration::from_secs(60))
+                .build()
+                .unwrap()
+        };
+        let mut game = cooperative();
+        //player 0's red 1 and player 1's red 2
+        game.arena
+            .piles
+            .push(Pile::from_vec(vec![0, 41], Color::Red));
+        assert!(game.should_end_round());
+        let round = game.end_round().unwrap();
+        assert!(round.is_game_over);
+        assert_eq!(round.team_won, Some(true));
+        assert_eq!(round.team_score, Some(-38));
+        assert_eq!(round.winner, None);
+
+        //the game is not scored again once it is over
+        assert!(!game.should_end_round());
+        assert!(game.end_round().is_err());
+        let transfer = Play {
+            player: 0,
+            play: Action::Player(PlayerAction::TransferToAvailable),
+        };
+        assert!(game.make_play(transfer).is_err());
+        assert_eq!(game.scoreboard.scores[0].len(), 1);
+
+        //calling blitz before the goal is reached is rejected, even when the caller's blitz pile is empty
+        let call_blitz = Play {
+            player: 0,
+            play: Action::CallBlitz(0),
+        };
+        for caller_is_out in [false, true] {
+            let mut game = cooperative();
+            if caller_is_out {
+                game.players[0].blitz_pile.clear();
+            }
+            assert!(!game.legal_moves(0).contains(&Action::CallBlitz(0)));
+            assert!(game.make_play(call_blitz).is_err());
+            assert!(!game.is_game_over);
+            assert!(game.scoreboard.scores[0].is_empty());
+        }
+
+        //once it is reached, calling blitz ends the game with the team's result
+        let mut game = cooperative();
+        game.arena
+            .piles
+            .push(Pile::from_vec(vec![0, 41], Color::Red));
+        assert!(game.legal_moves(0).contains(&Action::CallBlitz(0)));
+        let event = game.make_play(call_blitz).unwrap();
+        let proto::server_event::Event::RoundOver(round) = event else {
+            panic!("expected the game to end, got {event:?}");
+        };
+        assert!(round.is_game_over);
+        assert_eq!(round.team_won, Some(true));
+
+        let mut game = cooperative();
+        assert!(game.tick(Instant::now()).unwrap().is_empty());
+        let events = game.tick(Instant::now() + Duration::from_secs(61)).unwrap();
+        let [proto::server_event::Event::RoundOver(round)] = &events[..] else {
+            panic!("expected the game to end, got {events:?}");
+        };
+        assert!(round.is_game_over);
+        assert_eq!(round.team_won, Some(false));
+    }
+
+    #[test]
+    fn tables_reseat_players_by_combined_standings() {
+        let mut tables = tables::TableSet::new(12, GameStateBuilder::new().build_prefs()).unwrap();
+        assert_eq!(tables::TableSet::table_sizes(20), vec![7, 7, 6]);
+        assert_eq!(tables.tables[1].seats, vec![6, 7, 8, 9, 10, 11]);
+        //player 7 (seat 1 at the second table) does best, player 0 (seat 0 at the first table) worst
+        tables.tables[1].game.players[1].blitz_pile.clear();
+        tables.tables[0].game.players[0].blitz_pile.cards.push(0);
+        //only a player who is out may call blitz
+        assert!(tables.make_play(6, Action::CallBlitz(0)).is_err());
+        let (table, event) = tables.make_play(7, Action::CallBlitz(1)).unwrap();
+        let proto::server_event::Event::RoundOver(round_over) = event else {
+            panic!("expected the table's round to end, got {event:?}");
+        };
+        assert_eq!(table, 1);
+        assert!(round_over.player_cards.is_empty());
+        assert!(tables
+            .make_play(7, Action::Player(PlayerAction::ResetHand))
+            .is_err());
+        assert!(tables.rotate().is_err());
+        tables.end_table_round(0).unwrap();
+        let round_over = tables.rotate().unwrap();
+
+        assert_eq!(round_over.round, 0);
+        assert_eq!(round_over.tables[1].players, vec![6, 7, 8, 9, 10, 11]);
+        assert_eq!(round_over.tables[1].scores[1].player_id, 1);
+        assert_eq!(round_over.tables[1].scores[1].round_score, 0);
+        assert_eq!(round_over.scores[7].player_id, 7);
+        assert_eq!(round_over.scores[7].total_score, 0);
+        assert_eq!(round_over.standings[0], 7);
+        assert_eq!(*round_over.standings.last().unwrap(), 0);
+        assert!(!round_over.is_game_over);
+        assert_eq!(tables.round, 1);
+        assert_eq!(tables.scoreboard.get_totals()[7], 0);
+        assert_eq!(tables.tables[0].seats[0], 7);
+        assert_eq!(tables.tables[1].seats.last(), Some(&0));
+        assert!(!tables.tables[0].is_finished());
+
+        //sessions too large for one table are split when the game starts
+        let start = |session: &mut server::Session| {
+            session.start_game(proto::StartGameEvent {
+                player: Some(session.players[0].clone()),
+                preset: "classic".to_string(),
+                ..Default::default()
+            })
+        };
+        let mut large = session(20);
+        let started = start(&mut large).unwrap();
+        assert!(large.game_state.is_none() && large.is_in_game());
+        assert_eq!(large.tables.as_ref().unwrap().tables.len(), 3);
+        let seat = started[15].table_seat.as_ref().unwrap();
+        assert_eq!((seat.table, seat.seat), (2, 1));
+        assert_eq!(seat.players, vec![14, 15, 16, 17, 18, 19]);
+        assert_eq!(started[15].player_cards.len(), 6);
+        let small = start(&mut session(8)).unwrap();
+        assert!(small[0].table_seat.is_none());
+    }
+
+    #[test]
+    fn solo_game_records_moves_until_the_blitz_pile_is_empty() {
+        let solo = || GameStateBuilder::new().with_game_mode(proto::GameMode::Solo);
+        assert!(solo().build().is_err());
+        let mut game = solo().with_player_count(1).build().unwrap();
+        let play = |play| Play { player: 0, play };
+        game.make_play(play(Action::Player(PlayerAction::TransferToAvailable)))
+            .unwrap();
+        game.make_play(play(Action::Player(PlayerAction::ResetHand)))
+            .unwrap();
+        assert!(!game.should_end_round());
+
+        game.players[0].blitz_pile.clear();
+        assert!(game.should_end_round());
+        let round = game.end_round().unwrap();
+        assert!(round.is_game_over);
+        let completed = round.solo_result.unwrap();
+        assert!(completed.completed);
+        assert_eq!(completed.moves, 2);
+
+        //calling blitz with cards left gives the run up
+        let mut game = solo().with_player_count(1).build().unwrap();
+        game.make_play(play(Action::Player(PlayerAction::TransferToAvailable)))
+            .unwrap();
+        let event = game.make_play(play(Action::CallBlitz(0))).unwrap();
+        let proto::server_event::Event::RoundOver(round) = event else {
+            panic!("expected the run to end, got {event:?}");
+        };
+        assert!(round.is_game_over);
+        let given_up = round.solo_result.clone().unwrap();
+        assert!(!given_up.completed);
+        assert_eq!(given_up.moves, 1);
+
+        //and is still recorded for a daily challenge
+        let daily_results = daily::DailyResults::new();
+        let mut session = session(1);
+        session.daily_challenge = Some(7);
+        session.record_solo_result(&round, &daily_results);
+        let leaderboard = daily_results.leaderboard(7);
+        assert_eq!(leaderboard.results[0].username, "player 0");
+        assert_eq!(leaderboard.results[0].result, Some(given_up));
+    }
+
+    #[test]
+    fn undo_takes_back_plays_in_practice_games() {
+        let mut game = GameStateBuilder::new()
+            .with_game_mode(proto::GameMode::Practice)
+            .build()
+            .unwrap();
+        let play = |player, play| Play { player, play };
+        game.players[0].hand.available_to_play = vec![1, 0];
+        game.players[1].hand.available_to_play = vec![41];
+        let in_hand = game.players[0].hand.in_hand.clone();
+        game.make_play(play(0, Action::Player(PlayerAction::TransferToAvailable)))
+            .unwrap();
+        assert_ne!(game.players[0].hand.in_hand, in_hand);
+        game.undo(0).unwrap();
+        assert_eq!(game.players[0].hand.in_hand, in_hand);
+
+        //player 0 starts a pile with their red 1, which player 1 then covers with their red 2
+        game.make_play(play(0, Action::Arena(ArenaAction::FromAvailableHand(0))))
+            .unwrap();
+        game.make_play(play(1, Action::Arena(ArenaAction::FromAvailableHand(0))))
+            .unwrap();
+        assert!(game.undo(0).is_err());
+        game.undo(1).unwrap();
+        game.undo(0).unwrap();
+        assert!(game.arena.piles.is_empty());
+        assert_eq!(game.players[0].hand.available_to_play, vec![1, 0]);
+        assert!(game.undo(0).is_err());
+        assert_eq!(game.round_moves, 0);
+
+        //undone plays do not count toward a solo run
+        let mut game = GameStateBuilder::new()
+            .with_game_mode(proto::GameMode::Solo)
+            .with_player_count(1)
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            game.make_play(play(0, Action::Player(PlayerAction::TransferToAvailable)))
+                .unwrap();
+        }
+        game.undo(0).unwrap();
+        assert_eq!(game.solo_result().unwrap().moves, 1);
+
+        let mut game = GameStateBuilder::new().build().unwrap();
+        game.make_play(play(0, Action::Player(PlayerAction::TransferToAvailable)))
+            .unwrap();
+        assert!(game.undo(0).is_err());
+    }
+
+    #[test]
+    fn scenarios_load_exact_positions() {
+        let json = r#"{
+            "drawRate": 1,
+            "players": [
+                { "hand": ["R7", "G2"], "available": ["B2"], "post": [["Y9", "Y8"], ["G5"]], "blitz": ["R3", "R2"] },
+                { "post": [["B9"]], "blitz": ["Y1"] }
+            ],
+            "arena": [["0:R1"], ["1:B1"]]
+        }"#;
+        let scenario = scenario::Scenario::from_json(json).unwrap();
+        let mut game = scenario.load().unwrap();
+        assert_eq!(game.draw_rate, 1);
+        assert_eq!(game.players[0].post_pile.piles[0].cards, vec![38, 37]);
+        assert_eq!(game.players[1].blitz_pile.cards, vec![40 + 30]);
+        assert_eq!(
+            scenario::Scenario::capture(&game)
+                .load()
+                .unwrap()
+                .arena
+                .piles[1]
+                .cards,
+            vec![40 + 10]
+        );
+        //player 0's blue 2 goes on player 1's blue 1
+        game.make_play(Play {
+            player: 0,
+            play: Action::Arena(ArenaAction::FromAvailableHand(1)),
+        })
+        .unwrap();
+
+        let random = GameStateBuilder::new().build().unwrap();
+        let captured = scenario::Scenario::capture(&random);
+        assert_eq!(
+            scenario::Scenario::capture(&captured.load().unwrap()),
+            captured
+        );
+
+        for invalid in [
+            json.replace(r#""G2""#, r#""R7""#),
+            json.replace(r#""Y8""#, r#""Y7""#),
+            json.replace(r#""1:B1""#, r#""1:B2""#),
+            json.replace(r#""0:R1""#, r#""R1""#),
+        ] {
+            assert!(scenario::Scenario::from_json(&invalid)
+                .unwrap()
+                .load()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn hints_rank_blitz_plays_first() {
+        let game = scenario::Scenario::from_json(
+            r#"{
+                "gameMode": "PRACTICE",
+                "players": [
+                    { "hand": ["G4"], "available": ["Y1"], "post": [["B9"], ["Y5"]], "blitz": ["G7", "B8"] },
+                    { "post": [["R9"]], "blitz": ["R5"] }
+                ],
+                "arena": [["1:R1"]]
+            }"#,
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+        let hints = game.hint(0);
+        let plays: Vec<_> = hints.iter().map(|h| format!("{:?}", h.play)).collect();
+        //the blue 8 can only go on the blue 9, and the yellow 1 starts a single new arena pile
+        assert_eq!(
+            plays,
+            [
+                "Player(BlitzToPost(0))",
+                "Arena(FromAvailableHand(1))",
+                "Player(TransferToAvailable)",
+                "Player(ResetHand)",
+            ]
+        );
+        assert!(game.allows_hints());
+    }
+
+    #[test]
+    fn solver_finds_the_shortest_way_to_empty_the_blitz_pile() {
+        let position = |blitz: &str| {
+            scenario::Scenario::from_json(&format!(
+                r#"{{
+                    "drawRate": 1,
+                    "players": [
+                        {{ "hand": ["G6", "R2", "Y7"], "post": [["B9"]], "blitz": {blitz} }},
+                        {{ "post": [["Y9"]], "blitz": ["Y8"] }}
+                    ],
+                    "arena": [["1:R1"]]
+                }}"#
+            ))
+            .unwrap()
+            .load()
+            .unwrap()
+        };
+        //the red 2 is the second card drawn, and has to be played before the red 3 can follow it
+        let solution = solver::solve_blitz_pile(&position(r#"["R3"]"#), 0, 10_000);
+        let solver::Solution::Solved(moves) = solution else {
+            panic!("expected a solution, got {solution:?}");
+        };
+        assert_eq!(moves.len(), 4);
+        assert!(matches!(moves[3], Action::Arena(ArenaAction::FromBlitz(0))));
+
+        assert_eq!(
+            solver::solve_blitz_pile(&position(r#"["G9"]"#), 0, 10_000),
+            solver::Solution::Unsolvable
+        );
+        assert_eq!(
+            solver::solve_blitz_pile(&position(r#"["G9"]"#), 0, 2),
+            solver::Solution::GaveUp
+        );
+    }
+
+    #[test]
+    fn mcts_bot_finds_the_winning_move() {
+        let mut game = scenario::Scenario::from_json(
+            r#"{
+                "players": [
+                    { "hand": ["G6", "Y7", "B4", "G3"], "available": ["Y2"], "post": [["B9"]], "blitz": ["R2"] },
+                    { "hand": ["R5", "Y6", "B3"], "post": [["Y9"]], "blitz": ["G8", "Y8"] }
+                ],
+                "arena": [["1:R1"]]
+            }"#,
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+        let mut bot = bot::MctsBot::new()
+            .with_iterations(100)
+            .with_rollout_depth(2)
+            .with_seed(7);
+        let play = bot.choose_move(&game, 0).unwrap();
+        assert_eq!(play, Action::Arena(ArenaAction::FromBlitz(0)));
+        game.make_play(Play { player: 0, play }).unwrap();
+        assert_eq!(bot.choose_move(&game, 0), Some(Action::CallBlitz(0)));
+    }
+
+    #[test]
+    fn analysis_finds_missed_plays_and_time_spent_cycling() {
+        let mut game = scenario::Scenario::from_json(
+            r#"{
+                "drawRate": 1,
+                "players": [
+                    { "hand": ["G7", "G8"], "blitz": ["Y9", "R1"] },
+                    { "hand": ["Y4"], "blitz": ["B5"] }
+                ]
+            }"#,
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+        let mut recorder = analysis::GameRecorder::new(&game);
+        let started = recorder.rounds[0].started;
+        let plays = [
+            (6, Action::Arena(ArenaAction::FromBlitz(0))),
+            (7, Action::Player(PlayerAction::TransferToAvailable)),
+            (9, Action::Player(PlayerAction::TransferToAvailable)),
+        ];
+        for (seconds, play) in plays {
+            let play = Play { player: 0, play };
+            recorder.observe(&game);
+            game.make_play(play).unwrap();
+            recorder.record(&game, play, started + Duration::from_secs(seconds));
+        }
+
+        let analysis = recorder.analyze(&game, analysis::DEFAULT_IDLE_THRESHOLD);
+        assert_eq!(analysis.played_ms, 9000);
+        let player = &analysis.players[0];
+        assert_eq!(player.arena_cards, 1);
+        assert_eq!(player.transfers, 2);
+        assert_eq!(player.cycling_ms, 2000);
+        assert!((player.cards_per_minute - 1.0 / 0.15).abs() < 0.01);
+        assert_eq!(player.missed_opportunities.len(), 1);
+        let missed = &player.missed_opportunities[0];
+        assert_eq!((missed.at_ms, missed.waited_ms), (0, 6000));
+        assert!(analysis.players[1].missed_opportunities.is_empty());
+
+        //plays that were taken back are left out of the replay
+        let mut game = scenario::Scenario::from_json(
+            r#"{
+                "gameMode": "PRACTICE",
+                "players": [
+                    { "hand": ["G7"], "blitz": ["Y9", "R1"] },
+                    { "hand": ["Y4"], "blitz": ["B5"] }
+                ]
+            }"#,
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+        let mut recorder = analysis::GameRecorder::new(&game);
+        let started = recorder.rounds[0].started;
+        let play = Play {
+            player: 0,
+            play: Action::Arena(ArenaAction::FromBlitz(0)),
+        };
+        for seconds in [1, 3] {
+            recorder.observe(&game);
+            game.make_play(play).unwrap();
+            recorder.record(&game, play, started + Duration::from_secs(seconds));
+            if seconds == 1 {
+                game.undo(0).unwrap();
+                recorder.undo(&game, 0);
+            }
+        }
+        assert_eq!(recorder.rounds[0].plays.len(), 1);
+        let analysis = recorder.analyze(&game, analysis::DEFAULT_IDLE_THRESHOLD);
+        assert_eq!(analysis.played_ms, 3000);
+        assert_eq!(analysis.players[0].arena_cards, 1);
+
+        //the server analyses games once they are over
+        let mut game = scenario::Scenario::from_json(
+            r#"{
+                "drawRate": 1,
+                "gameMode": "SOLO",
+                "players": [{ "hand": ["G7", "G8"], "blitz": ["Y9", "R1"] }]
+            }"#,
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+        let mut recorder = analysis::GameRecorder::new(&game);
+        let started = recorder.rounds[0].started;
+        let plays = [
+            (2, Action::Arena(ArenaAction::FromBlitz(0))),
+            (4, Action::Player(PlayerAction::TransferToAvailable)),
+        ];
+        for (seconds, play) in plays {
+            let play = Play { player: 0, play };
+            recorder.observe(&game);
+            game.make_play(play).unwrap();
+            recorder.record(&game, play, started + Duration::from_secs(seconds));
+        }
+        assert!(game.end_round().unwrap().is_game_over);
+        let analysis = recorder.analyze(&game, analysis::DEFAULT_IDLE_THRESHOLD);
+        assert_eq!(analysis.played_ms, 4000);
+        assert_eq!(analysis.players[0].arena_cards, 1);
+        assert_eq!(analysis.players[0].transfers, 1);
+    }
+
+    #[test]
+    fn gym_env_deals_by_seed_and_maps_every_action() {
+        let prefs = GameStateBuilder::new()
+            .with_auto_end_round(true)
+            .with_win_condition(proto::WinCondition::FixedRounds)
+            .with_round_limit(1)
+            .build_prefs();
+        let mut env = gym::BlitzEnv::new(2, prefs).unwrap();
+        let first = env.reset(11).unwrap();
+        assert_eq!(first[0].len(), env.observation_size());
+        assert_eq!(env.reset(11).unwrap(), first);
+        assert_ne!(env.reset(12).unwrap(), first);
+
+        for i in 0..env.action_count() {
+            let action = env.action(i).unwrap();
+            assert_eq!(env.action_index(action), Some(i));
+        }
+        assert!(env.action(env.action_count()).is_none());
+
+        //play the best hint until the game ends, collecting the rewards
+        let mut rewards = [0.0; 2];
+        let mut done = false;
+        for turn in 0..5000 {
+            let player = turn % 2;
+            let hints = env.game.hint(player);
+            let action = env.action_index(hints[0].play).unwrap();
+            assert!(env.action_mask(player)[action]);
+            let step = env.step(player, action).unwrap();
+            rewards[player as usize] += step.reward;
+            if step.done {
+                done = true;
+                break;
+            }
+        }
+        assert!(done);
+        let totals = env.game.scoreboard.get_totals();
+        assert_eq!(rewards, [totals[0] as f32, totals[1] as f32]);
+    }
+
+    #[test]
+    #[cfg(feature = "python")]
+    fn python_bindings_create_play_and_read_games() {
+        use pyo3::prelude::*;
+        use pyo3::types::PyDict;
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new(py);
+            let builder = py.get_type::<python::PyGameStateBuilder>();
+            globals.set_item("GameStateBuilder", builder).unwrap();
+            globals
+                .set_item("Play", py.get_type::<python::PyPlay>())
+                .unwrap();
+            let script = c"
+game = GameStateBuilder().with_player_count(2).with_seed(7).build()
+assert game.player_count == 2
+assert len(game.blitz_pile(0)) == 10 and len(game.post_piles(0)) == 3
+assert len(game.hand(0)) == 27 and game.available(0) == []
+
+transfer = Play.transfer_to_available(0)
+assert transfer in game.legal_moves(0)
+game.make_play(transfer)
+assert len(game.hand(0)) == 24 and len(game.available(0)) == 3
+try:
+    game.make_play(Play.from_blitz(0, 99))
+    raise AssertionError('an arena pile that does not exist was played on')
+except ValueError:
+    pass
+
+saved = game.snapshot()
+game.make_play(transfer)
+game.restore(saved)
+assert len(game.available(0)) == 3
+assert type(game).from_json(game.to_json()).available(0) == game.available(0)
+assert not game.is_game_over() and game.scoreboard.totals() == [0, 0]
+
+coop = (
+    GameStateBuilder()
+    .with_player_count(3)
+    .with_game_mode('COOPERATIVE')
+    .with_time_limit_seconds(300)
+    .with_cooperative_arena_goal(30)
+    .with_post_refill_rule('FORCED_REFILL')
+    .with_misplay_penalty(2)
+    .with_misplay_lockout_ms(1500)
+    .with_draw_rate_schedule([(0, None, 2), (1, 60, 1)])
+    .build()
+)
+assert '\"gameMode\": \"COOPERATIVE\"' in coop.to_json()
+try:
+    GameStateBuilder().with_post_refill_rule('SOMETIMES')
+    raise AssertionError('an unknown post refill rule was accepted')
+except ValueError:
+    pass
+";
+            py.run(script, Some(&globals), None).unwrap();
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "ffi")]
+    fn ffi_plays_and_snapshots_games() {
+        use prost::Message;
+        let prefs = GameStateBuilder::new().build_prefs().encode_to_vec();
+        unsafe {
+            let game = ffi::blitz_game_new(prefs.as_ptr(), prefs.len(), 2, 3);
+            assert!(!game.is_null());
+            let mut blitz = [0u32; 10];
+            let kind = ffi::BLITZ_PILE_BLITZ;
+            let len = ffi::blitz_game_pile(game, 0, kind, 0, blitz.as_mut_ptr(), blitz.len());
+            assert_eq!(len, 10);
+            assert_eq!(ffi::blitz_game_pile_count(game, 0, ffi::BLITZ_PILE_POST), 3);
+
+            let play = proto::Play {
+                player_id: 0,
+                play: Some(proto::play::Play::PlayerPlay(proto::PlayerPlay {
+                    play_type: proto::PlayerPlayType::TransferToAvailableHand as i32,
+                    ..Default::default()
+                })),
+            }
+            .encode_to_vec();
+            assert_eq!(
+                ffi::blitz_game_make_play(game, play.as_ptr(), play.len()),
+                0
+            );
+            let play = &play[..1];
+            assert_eq!(
+                ffi::blitz_game_make_play(game, play.as_ptr(), play.len()),
+                ffi::BLITZ_ERROR
+            );
+            assert!(!std::ffi::CStr::from_ptr(ffi::blitz_last_error()).is_empty());
+
+            let size = ffi::blitz_game_snapshot(game, std::ptr::null_mut(), 0);
+            let mut bytes = vec![0u8; size as usize];
+            assert_eq!(
+                ffi::blitz_game_snapshot(game, bytes.as_mut_ptr(), bytes.len()),
+                size
+            );
+            let snapshot = proto::GameSnapshot::decode(&bytes[..]).unwrap();
+            assert_eq!(snapshot.players[0].blitz_pile, blitz);
+            assert_eq!(
+                snapshot.players[0]
+                    .hand
+                    .as_ref()
+                    .unwrap()
+                    .available_to_play
+                    .len(),
+                3
+            );
+            ffi::blitz_game_free(game);
+        }
+    }
+
+    #[test]
+    fn compact_game_follows_the_same_rules() {
+        use rand::{seq::SliceRandom, SeedableRng};
+        let rules = [
+            proto::PostRefillRule::ManualRefill,
+            proto::PostRefillRule::AutomaticRefill,
+            proto::PostRefillRule::ForcedRefill,
+        ];
+        for (seed, rule) in rules.into_iter().enumerate() {
+            let mut game = GameStateBuilder::new()
+                .with_player_count(3)
+                .with_wild_cards_per_player(2)
+                .with_post_refill_rule(rule)
+                .with_seed(seed as u64)
+                .build()
+                .unwrap();
+            let mut compact = compact::CompactGame::from_game(&game).unwrap();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed as u64);
+            for turn in 0..3000 {
+                let player = turn % 3;
+                let mut moves = game.candidate_moves(player);
+                moves.retain(|play| !matches!(play, Action::CallBlitz(_)));
+                //try some plays that name piles that do not exist
+                moves.push(Action::Arena(ArenaAction::FromBlitz(40)));
+                let play = Play {
+                    player,
+                    play: *moves.choose(&mut rng).unwrap(),
+                };
+                let expected = game.make_play(play).is_ok();
+                assert_eq!(compact.make_play(play).is_ok(), expected, "{play:?}");
+                let mut written = game.clone();
+                compact.write_to(&mut written);
+                assert_eq!(written.snapshot(), game.snapshot());
+            }
+        }
+
+        //the largest games that pass validation fit as well
+        let game = GameStateBuilder::new()
+            .with_player_count(prefs::MAX_PLAYERS)
+            .with_wild_cards_per_player(prefs::MAX_WILD_CARDS_PER_PLAYER)
+            .with_post_pile_size(prefs::MAX_POST_PILE_SIZE)
+            .with_draw_rate(1)
+            .build()
+            .unwrap();
+        let compact = compact::CompactGame::from_game(&game).unwrap();
+        assert_eq!(
+            compact.players()[0].post_piles().len(),
+            prefs::MAX_POST_PILE_SIZE as usize
+        );
+        let mut written = game.clone();
+        compact.write_to(&mut written);
+        assert_eq!(written.snapshot(), game.snapshot());
+    }
+
+    #[test]
+    fn daily_challenge_deals_by_day_and_keeps_each_players_best_run() {
+        let day =
+            daily::day_of(std::time::UNIX_EPOCH + std::time::Duration::from_secs(86_400 * 3 + 60));
+        assert_eq!(day, 3);
+        let deal = |day| daily::new_game(day).unwrap().snapshot();
+        assert_eq!(deal(day), deal(day));
+        assert_ne!(deal(day), deal(day + 1));
+        assert_eq!(
+            daily::new_game(day).unwrap().game_mode,
+            proto::GameMode::Solo
+        );
+
+        let run = |completed, completion_ms, moves| proto::SoloResult {
+            completed,
+            completion_ms,
+            moves,
+        };
+        let results = daily::DailyResults::new();
+        results.record(day, "ana", run(true, 9000, 40)).unwrap();
+        results.record(day, "ana", run(true, 12000, 30)).unwrap();
+        results.record(day, "ben", run(false, 1000, 5)).unwrap();
+        results.record(day, "cy", run(true, 9000, 35)).unwrap();
+        results.record(day, "ben", run(true, 20000, 60)).unwrap();
+        results.record(day + 1, "dee", run(true, 1, 1)).unwrap();
+
+        let leaderboard = results.leaderboard(day);
+        assert_eq!(leaderboard.day, day);
+        let ranked: Vec<_> = leaderboard
+            .results
+            .iter()
+            .map(|r| (r.username.as_str(), r.result.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("cy", run(true, 9000, 35)),
+                ("ana", run(true, 9000, 40)),
+                ("ben", run(true, 20000, 60)),
+            ]
+        );
+        assert!(results.leaderboard(day + 2).results.is_empty());
+
+        //runs kept in a file are back on the leaderboard after a restart
+        let path = std::env::temp_dir().join(format!("blitz_daily_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let open = || daily::DailyResults::with_store(daily::FileStore::open(&path).unwrap());
+        let results = open().unwrap();
+        results.record(day, "ana", run(true, 9000, 40)).unwrap();
+        results.record(day, "ana", run(true, 12000, 30)).unwrap();
+        results.record(day, "ben", run(false, 1000, 5)).unwrap();
+        let leaderboard = results.leaderboard(day);
+        drop(results);
+        assert_eq!(open().unwrap().leaderboard(day), leaderboard);
+        assert_eq!(leaderboard.results.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    ///A session waiting to start, with player 0 as its admin.
+    fn session(player_count: u32) -> server::Session {
+        let players = (0..player_count)
+            .map(|player_game_id| proto::Player {
+                username: format!("player {player_game_id}"),
+                player_game_id,
+                is_session_admin: player_game_id == 0,
+                ..Default::default()
+            })
+            .collect();
+        server::Session {
+            server_event_counter: Default::default(),
+            in_flight_events: Default::default(),
+            id: "session".to_string(),
+            is_joinable: true,
+            game_state: None,
+            tables: None,
+            recorder: None,
+            daily_challenge: None,
+            players,
+            client_event_channels: (0..player_count).map(|_| (None, None)).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn broadcast_skips_players_whose_stream_has_closed() {
+        let (open_tx, open_rx) = flume::unbounded();
+        let (closed_tx, closed_rx) = flume::unbounded();
+        drop(closed_rx);
+        let mut session = session(2);
+        session.client_event_channels = vec![(Some(closed_tx), None), (Some(open_tx), None)];
+        let event = proto::server_event::Event::ChangeDrawRate(Default::default());
+        server::Server::broadcast_to_all(event, &session)
+            .await
+            .unwrap();
+        assert_eq!(open_rx.len(), 1);
+        assert!(!session.in_flight_events.contains_key(&0));
+        assert_eq!(*session.in_flight_events.get(&1).unwrap(), [0]);
+    }
+
+    #[tokio::test]
+    async fn server_only_penalizes_plays_that_break_the_arena_rules() {
+        let mut session = session(3);
+        let mut game = GameStateBuilder::new()
+            .with_player_count(3)
+            .with_misplay_penalty(3)
+            .with_misplay_lockout(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        //a red 5 cannot start an arena pile
+        for player in 0..3 {
+            game.players[player].blitz_pile = BlitzPile::new(vec![player as u32 * 40 + 4]);
+        }
+        game.players[2].eliminated_in_round = Some(0);
+        session.game_state = Some(game);
+        let misplay = |player| Play {
+            player,
+            play: Action::Arena(ArenaAction::FromBlitz(0)),
+        };
+        let play = |session: &mut server::Session, player| {
+            let play = misplay(player);
+            let err = session
+                .game_state
+                .as_mut()
+                .unwrap()
+                .make_play(play)
+                .unwrap_err();
+            (play, err)
+        };
+
+        let (misplay_0, err) = play(&mut session, 0);
+        server::Server::reject_play(&mut session, 0, misplay_0, &err)
+            .await
+            .unwrap();
+        //locked out
+        let (misplay_0, err) = play(&mut session, 0);
+        server::Server::reject_play(&mut session, 0, misplay_0, &err)
+            .await
+            .unwrap();
+        //eliminated
+        let (misplay_2, err) = play(&mut session, 2);
+        server::Server::reject_play(&mut session, 2, misplay_2, &err)
+            .await
+            .unwrap();
+
+        let game = session.game_state.as_ref().unwrap();
+        assert_eq!(game.round_penalties, [-3, 0, 0]);
+        assert!(game.is_locked_out(0));
+        assert!(!game.is_locked_out(2));
+    }
 }